@@ -0,0 +1,47 @@
+use crate::vector::*;
+// apples that turn up as well as the usual one. each is eaten like any apple, counting
+// towards the goal and the combo, then does something of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppleKind {
+    Normal,
+    // grows the snake `GOLDEN_GROWTH` and is worth that many apples.
+    Golden,
+    // takes `SHRINK` off the tail instead of growing it.
+    Shrinking,
+    // speeds the game up for `HASTE_TICKS`.
+    Speed,
+    // worth double, but rots away after `ROT_TICKS`.
+    Timed
+}
+pub const GOLDEN_GROWTH: usize = 3;
+pub const SHRINK: usize = 2;
+pub const HASTE_TICKS: usize = 30;
+pub const ROT_TICKS: usize = 40;
+// the most extra apples a board can have out at once.
+pub const MAX_EXTRAS: usize = 16;
+impl AppleKind {
+    pub const ALL: [AppleKind; 5] = [AppleKind::Normal, AppleKind::Golden, AppleKind::Shrinking, AppleKind::Speed, AppleKind::Timed];
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppleKind::Normal    => "normal",
+            AppleKind::Golden    => "golden",
+            AppleKind::Shrinking => "shrinking",
+            AppleKind::Speed     => "speed",
+            AppleKind::Timed     => "timed"
+        }
+    }
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.name() == text)
+    }
+    // whether the snake that eats it gets longer.
+    pub fn grows(&self) -> bool {
+        *self != AppleKind::Shrinking
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Apple {
+    pub pos: IVec3,
+    pub kind: AppleKind,
+    // ticks until a timed apple rots, none for the rest.
+    pub ticks_left: Option<usize>
+}
@@ -0,0 +1,141 @@
+use crate::{snake::*, vector::*, apple::*, bot};
+// plays a perfect game by following a cycle through every cell of the board. the snake
+// stays in the order of the cycle from its tail to its head, so the cells ahead of its
+// head up to its tail are always free and it can't box itself in. it cuts ahead along
+// the cycle towards the apple as long as it stays short of its tail. a golden apple that
+// turns up between its head and tail with too little room left to grow can't be got round.
+//
+// the cycle is a path through one layer of the board, taken up and down the layers:
+// up the layers at the first cell of the path, back and forth up and down them for
+// every cell after, then back along the bottom layer to the start. that needs an even
+// number of cells in the layer, so a board with an odd side picks an even pair of sides.
+//
+// a board with every side odd has an odd number of cells, and no cycle goes through an
+// odd number of cells on a grid. the corner at 0 0 0 is left out and can take the place
+// of the cell on the cycle between two of its neighbours, so the snake still gets to it.
+pub struct Autopilot {
+    size: IVec3,
+    // each cell's place on the cycle, by x, then y, then z.
+    places: Vec<u32>,
+    len: usize,
+    // the corner left out of odd boards and the place it can stand in for.
+    spare: Option<(IVec3, usize)>
+}
+impl Autopilot {
+    // none if a side is shorter than 2, which levels can't be.
+    pub fn new(width: usize, depth: usize, height: usize) -> Option<Self> {
+        let sides = [width, depth, height];
+        if sides.iter().any(|i| *i < 2) { return None }
+        let odd = sides.iter().all(|i| i % 2 == 1);
+        // the two axes the layer is on and the one the layers stack along
+        let (a, b, c) = if odd || (width * depth).is_multiple_of(2) { (0, 1, 2) } else { (0, 2, 1) };
+        let cell = |u: usize, v: usize, w: usize| {
+            let mut pos = [0; 3];
+            (pos[a], pos[b], pos[c]) = (u as i32, v as i32, w as i32);
+            ivec3(pos[0], pos[1], pos[2])
+        };
+        // up and down the columns of the layer, starting next to the corner on odd boards
+        let mut layer = vec![];
+        for u in 0..sides[a] {
+            for v in 0..sides[b] {
+                let v = if u.is_multiple_of(2) { v } else { sides[b] - 1 - v };
+                if !(odd && u == 0 && v == 0) { layer.push((u, v)) }
+            }
+        }
+        let layers = sides[c];
+        let mut cycle = vec![];
+        for (i, (u, v)) in layer.iter().enumerate() {
+            for w in 1..layers {
+                let w = if i.is_multiple_of(2) { w } else { layers - w };
+                cycle.push(cell(*u, *v, w));
+                // on odd boards the column above the corner is taken in pairs on the way up
+                if odd && i == 0 && w % 2 == 1 && w + 1 < layers {
+                    cycle.extend([cell(0, 0, w), cell(0, 0, w + 1)]);
+                }
+            }
+        }
+        cycle.extend(layer.iter().rev().map(|(u, v)| cell(*u, *v, 0)));
+
+        let size = ivec3(width as i32, depth as i32, height as i32);
+        let mut places = vec![0; width * depth * height];
+        for (place, pos) in cycle.iter().enumerate() {
+            places[index(size, *pos)] = place as u32;
+        }
+        let len = cycle.len();
+        let spare = odd.then(|| {
+            let corner = IVec3::ZERO;
+            let place = (0..len)
+                .find(|i| cycle[(i + len - 1) % len].manhattan(corner) == 1 && cycle[(i + 1) % len].manhattan(corner) == 1)
+                .unwrap();
+            places[0] = place as u32;
+            (corner, place)
+        });
+        Some(Self { size, places, len, spare })
+    }
+    // every cell in the order of the cycle, not counting the spare one.
+    pub fn cycle(&self) -> Vec<IVec3> {
+        let mut cycle = vec![IVec3::ZERO; self.len];
+        for z in 0..self.size.z { for y in 0..self.size.y { for x in 0..self.size.x {
+            let pos = ivec3(x, y, z);
+            if self.spare.is_some_and(|i| i.0 == pos) { continue }
+            cycle[self.place(pos)] = pos;
+        } } }
+        cycle
+    }
+    pub fn spare(&self) -> Option<IVec3> {
+        self.spare.map(|i| i.0)
+    }
+    fn place(&self, pos: IVec3) -> usize {
+        self.places[index(self.size, pos)] as usize
+    }
+    // the direction for snake `index`, which should be the only one on a board without
+    // walls. anything else falls back to the careful bot when the cycle gets blocked.
+    pub fn pick(&self, board: &Board, index: usize) -> Direction {
+        let snake = &board.snakes[index];
+        let ahead = |from: usize, to: usize| (to + self.len - from) % self.len;
+        let head = self.place(snake.pos);
+        // how far the head can go along the cycle before it reaches the tail. a snake of
+        // one leaves a gap, it couldn't turn back into its tail once it's two long
+        let room = match snake.body.len() {
+            1 => self.len - 1,
+            _ => ahead(head, self.place(snake.body[0]))
+        };
+        let apple = self.place(board.apple_pos);
+        let spare = |pos: IVec3| self.spare.is_some_and(|i| i.0 == pos);
+        // ticks the tail stays put for from moving to `pos` on, while the snake grows. the
+        // head has to have room to keep going for all of them
+        let stays = |pos: IVec3| match board.grows_at(pos) {
+            true => {
+                let golden = board.extras.iter().any(|i| i.pos == pos && i.kind == AppleKind::Golden);
+                1 + snake.growth + if golden { GOLDEN_GROWTH - 1 } else { 0 }
+            },
+            false => snake.growth
+        };
+        Direction::ALL.into_iter()
+            .filter(|i| board.allow_reverse || !board.is_reverse(index, *i))
+            .map(|i| (i, board.next_pos(index, i)))
+            .filter(|(_, pos)| bot::is_safe(board, index, *pos))
+            .filter(|(_, pos)| {
+                let skip = ahead(head, self.place(*pos));
+                // following the tail into its place is fine as long as it moves. cutting
+                // ahead right up to it would keep the snake a whole lap long and going
+                // round the same way forever
+                let fits = match stays(*pos) {
+                    0 => skip < room || skip == 1 && room == 1,
+                    stays => skip + stays <= room
+                };
+                // nothing matters after the apple that fills the board
+                let last = *pos == board.apple_pos && snake.body.len() + 1 >= board.free_cells();
+                // the spare corner only stands in for its place, it's no shortcut
+                skip > 0 && (skip == 1 || !spare(*pos)) && (fits || last)
+            })
+            // as close to the apple as it gets without passing it, keeping off the spare
+            // corner unless the apple's there
+            .min_by_key(|(_, pos)| (ahead(self.place(*pos), apple), *pos != board.apple_pos, spare(*pos)))
+            .map(|i| i.0)
+            .unwrap_or_else(|| bot::careful(board, index))
+    }
+}
+fn index(size: IVec3, pos: IVec3) -> usize {
+    (pos.x + pos.y * size.x + pos.z * size.x * size.y) as usize
+}
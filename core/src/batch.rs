@@ -0,0 +1,105 @@
+use crate::{snake::*, level::*, controller::Controller};
+use std::collections::BTreeMap;
+// plays a level over and over with no window, each snake steered by a controller, and
+// adds up how the games went. each game is seeded one on from the last.
+pub struct Batch {
+    pub level: Level,
+    pub games: usize,
+    pub seed: u64,
+    pub speed: i8,
+    pub allow_reverse: bool,
+    // games that go on this long are assumed to be going round in circles and are cut off.
+    pub max_ticks: usize
+}
+// how a batch went. the lengths, scores and deaths are player one's.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+    pub games: usize,
+    pub wins: usize,
+    // games that ended with nobody winning.
+    pub losses: usize,
+    pub stuck: usize,
+    // each snake's wins.
+    pub winners: Vec<usize>,
+    // how many games ended at each length.
+    pub lengths: BTreeMap<usize, usize>,
+    pub ticks: usize,
+    // games player one died in, how many ticks it lasted in them altogether, and what
+    // it died of, by `Death::ALL`.
+    pub deaths: usize,
+    pub death_ticks: usize,
+    pub causes: [usize; Death::ALL.len()],
+    pub score: u64,
+    pub max_score: u64,
+    pub best_combo: u32
+}
+impl Batch {
+    pub fn new(level: Level, games: usize) -> Self {
+        let cells = level.width * level.depth * level.height;
+        let speed = level.speed.unwrap_or(3);
+        Self { level, games, seed: 0, speed, allow_reverse: false, max_ticks: cells * cells + 1000 }
+    }
+    // `controllers` has one for each of the level's snakes.
+    pub fn run(&self, controllers: &mut [Box<dyn Controller + '_>]) -> Stats {
+        assert_eq!(controllers.len(), self.level.snakes.len(), "every snake needs a controller");
+        let mut stats = Stats { winners: vec![0; self.level.snakes.len()], ..Stats::default() };
+        for game in 0..self.games {
+            let mut board = self.level.board(self.seed.wrapping_add(game as u64));
+            board.speed = self.speed;
+            board.allow_reverse = self.allow_reverse;
+            let mut ticks = 0;
+            let mut died = None;
+            while board.state == State::Alive && ticks < self.max_ticks {
+                for (i, controller) in controllers.iter_mut().enumerate() {
+                    if board.snakes[i].alive { board.snakes[i].direction = controller.pick(&board, i) }
+                }
+                board.update();
+                ticks += 1;
+                if died.is_none() && !board.snakes[0].alive { died = Some(ticks) }
+            }
+            stats.add(&board, ticks, died);
+        }
+        stats
+    }
+}
+impl Stats {
+    fn add(&mut self, board: &Board, ticks: usize, died: Option<usize>) {
+        self.games += 1;
+        match board.state {
+            State::Win => self.wins += 1,
+            State::Dead => self.losses += 1,
+            _ => self.stuck += 1
+        }
+        if let Some(i) = board.winner { self.winners[i] += 1 }
+        let snake = &board.snakes[0];
+        *self.lengths.entry(snake.body.len()).or_default() += 1;
+        self.ticks += ticks;
+        if let (Some(ticks), Some(death)) = (died, snake.death) {
+            self.deaths += 1;
+            self.death_ticks += ticks;
+            self.causes[Death::ALL.iter().position(|i| *i == death).unwrap()] += 1;
+        }
+        self.score += snake.score.points;
+        self.max_score = self.max_score.max(snake.score.points);
+        self.best_combo = self.best_combo.max(snake.score.best_combo);
+    }
+    pub fn win_rate(&self) -> f32 {
+        self.wins as f32 / self.games.max(1) as f32
+    }
+    pub fn average_length(&self) -> f32 {
+        self.lengths.iter().map(|(length, games)| length * games).sum::<usize>() as f32 / self.games.max(1) as f32
+    }
+    pub fn max_length(&self) -> usize {
+        self.lengths.keys().last().copied().unwrap_or(0)
+    }
+    pub fn average_ticks(&self) -> f32 {
+        self.ticks as f32 / self.games.max(1) as f32
+    }
+    // none if it never died.
+    pub fn average_ticks_to_death(&self) -> Option<f32> {
+        (self.deaths > 0).then(|| self.death_ticks as f32 / self.deaths as f32)
+    }
+    pub fn average_score(&self) -> f32 {
+        self.score as f32 / self.games.max(1) as f32
+    }
+}
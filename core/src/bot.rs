@@ -0,0 +1,142 @@
+use crate::{snake::*, IVec3};
+use std::collections::{HashMap, HashSet, VecDeque};
+// how well a computer snake plays. each one just picks a direction for its snake every
+// tick, so they can be fed in wherever a player's turns go.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Difficulty {
+    // heads straight for the apple.
+    Easy,
+    // takes the shortest way round to the apple.
+    #[default]
+    Medium,
+    // only goes for the apple if it won't be boxed in once it gets there.
+    Hard
+}
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy   => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard   => "hard"
+        }
+    }
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.name() == text)
+    }
+    // the direction snake `index` should head next.
+    pub fn pick(&self, board: &Board, index: usize) -> Direction {
+        match self {
+            Difficulty::Easy   => greedy(board, index),
+            Difficulty::Medium => pathfind(board, index),
+            Difficulty::Hard   => careful(board, index)
+        }
+    }
+}
+// true if snake `index` can move into `pos` next tick without dying. tails count as
+// free since they move out of the way, unless that snake is about to grow or eat.
+pub fn is_safe(board: &Board, index: usize, pos: IVec3) -> bool {
+    match board.point(pos) {
+        Some(Point::Empty) => true,
+        Some(Point::Snake) => (0..board.snakes.len()).any(|i| {
+            let snake = &board.snakes[i];
+            // a snake going into its own tail isn't eating
+            let head = if i == index { pos } else { next_head(board, i) };
+            snake.alive && snake.growth == 0 && snake.body[0] == pos && !board.grows_at(head)
+        }),
+        Some(Point::Wall) | None => false
+    }
+}
+// where snake `index` will move next tick, going by the turn it has waiting the same
+// way `Board::update` does.
+fn next_head(board: &Board, index: usize) -> IVec3 {
+    let snake = &board.snakes[index];
+    let direction = snake.queue.front().copied().unwrap_or(snake.direction);
+    if board.is_reverse(index, direction) && !board.allow_reverse {
+        board.next_pos(index, direction.opposite())
+    } else {
+        board.next_pos(index, direction)
+    }
+}
+// heads snake `index` straight for the apple, only avoiding moves that die next tick.
+pub fn greedy(board: &Board, index: usize) -> Direction {
+    Direction::ALL.into_iter()
+        .filter(|i| is_safe(board, index, board.next_pos(index, *i)))
+        // a reverse would just carry on straight
+        .filter(|i| board.allow_reverse || !board.is_reverse(index, *i))
+        .min_by_key(|i| board.next_pos(index, *i).manhattan(board.apple_pos))
+        .unwrap_or(board.snakes[index].direction)
+}
+// follows the shortest path to the apple around walls and snakes, falling back to
+// greedy when there isn't one.
+pub fn pathfind(board: &Board, index: usize) -> Direction {
+    let empty = |pos| board.point(pos) == Some(Point::Empty);
+    path(board, board.snakes[index].pos, |pos| pos == board.apple_pos, empty)
+        .and_then(|i| i.first().copied())
+        .unwrap_or_else(|| greedy(board, index))
+}
+// like pathfind, but only takes the path if the snake could still reach its own tail
+// after eating, so it can always follow it round. otherwise it makes the safe move
+// that keeps its tail furthest off, until the way to the apple opens up. a snake that
+// has gone round twice its length without one takes the risk rather than loop forever.
+pub fn careful(board: &Board, index: usize) -> Direction {
+    let snake = &board.snakes[index];
+    let empty = |pos| board.point(pos) == Some(Point::Empty);
+    if let Some(path) = path(board, snake.pos, |pos| pos == board.apple_pos, empty) {
+        let hungry = board.ticks - snake.score.last_apple().unwrap_or(0) > snake.body.len() * 2;
+        if hungry || tail_distance(board, index, &path).is_some() { return path[0] }
+    }
+    Direction::ALL.into_iter()
+        .filter(|i| is_safe(board, index, board.next_pos(index, *i)))
+        .filter(|i| board.allow_reverse || !board.is_reverse(index, *i))
+        .filter_map(|i| Some((i, tail_distance(board, index, &[i])?)))
+        .max_by_key(|(i, distance)| (*distance, board.next_pos(index, *i).manhattan(board.apple_pos)))
+        .map(|i| i.0)
+        .unwrap_or_else(|| greedy(board, index))
+}
+// whether snake `index` could get from its head to its tail after making `moves`, with
+// the rest of the board as it is now.
+fn tail_distance(board: &Board, index: usize, moves: &[Direction]) -> Option<usize> {
+    let snake = &board.snakes[index];
+    let mut body: VecDeque<IVec3> = snake.body.iter().copied().collect();
+    let mut head = snake.pos;
+    for i in moves {
+        head = board.wrap_pos(head + i.dir());
+        body.push_back(head);
+        if !board.grows_at(head) { body.pop_front(); }
+    }
+    let tail = body[0];
+    // a snake of one can always get out of the way of itself
+    if tail == head { return Some(0) }
+    let body: HashSet<IVec3> = body.into_iter().collect();
+    // cells the snake has moved off are free too
+    let free = |pos| !body.contains(&pos) && (board.point(pos) == Some(Point::Empty) || snake.body.contains(&pos));
+    path(board, head, |pos| pos == tail, free).map(|i| i.len())
+}
+// the directions from `from` to the nearest cell `goal` accepts, only going through
+// cells `free` accepts. breadth first so it's as short as can be.
+fn path(board: &Board, from: IVec3, goal: impl Fn(IVec3) -> bool, free: impl Fn(IVec3) -> bool) -> Option<Vec<Direction>> {
+    let mut came_from: HashMap<IVec3, (IVec3, Direction)> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(pos) = queue.pop_front() {
+        for direction in Direction::ALL {
+            let next = board.wrap_pos(pos + direction.dir());
+            if next == from || came_from.contains_key(&next) { continue }
+            if goal(next) {
+                let mut path = vec![direction];
+                let mut pos = pos;
+                while pos != from {
+                    let (previous, direction) = came_from[&pos];
+                    path.push(direction);
+                    pos = previous;
+                }
+                path.reverse();
+                return Some(path)
+            }
+            if !free(next) { continue }
+            came_from.insert(next, (pos, direction));
+            queue.push_back(next);
+        }
+    }
+    None
+}
@@ -0,0 +1,62 @@
+use crate::level::*;
+use std::{fs, io, path::Path};
+// an ordered list of levels where beating one unlocks the next. the list lives in
+// `campaign.txt` in the level folder, one file name per line.
+pub struct Campaign {
+    pub levels: Vec<Level>,
+    // each level's file name in the list, which tells them apart where names might not.
+    pub files: Vec<String>,
+    pub completed: Vec<bool>
+}
+impl Campaign {
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, LevelError> {
+        let dir = dir.as_ref();
+        let list = dir.join("campaign.txt");
+        let text = fs::read_to_string(&list)
+            .map_err(|i| LevelError { line: 0, message: format!("couldn't read {}: {i}", list.display()) })?;
+        let (mut levels, mut files) = (vec![], vec![]);
+        for file in text.lines().map(|i| i.split("//").next().unwrap().trim()).filter(|i| !i.is_empty()) {
+            let level = Level::load(dir.join(file))
+                .map_err(|i| LevelError { line: i.line, message: format!("{file}: {}", i.message) })?;
+            levels.push(level);
+            files.push(file.to_string());
+        }
+        let completed = vec![false; levels.len()];
+        Ok(Self { levels, files, completed })
+    }
+    pub fn unlocked(&self, index: usize) -> bool {
+        index < self.levels.len() && (index == 0 || self.completed[index - 1] || self.completed[index])
+    }
+    // the last level that can be played
+    pub fn furthest(&self) -> usize {
+        (0..self.levels.len()).rev().find(|i| self.unlocked(*i)).unwrap_or(0)
+    }
+    pub fn complete(&mut self, index: usize) {
+        if let Some(i) = self.completed.get_mut(index) { *i = true }
+    }
+    // progress is saved as the file names of beaten levels so it survives levels being
+    // added or moved around in the list.
+    pub fn save_progress(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text: String = self.files.iter().zip(&self.completed)
+            .filter(|i| *i.1)
+            .map(|i| format!("completed {}\n", i.0))
+            .collect();
+        if let Some(dir) = path.as_ref().parent() { fs::create_dir_all(dir)? }
+        fs::write(path, text)
+    }
+    pub fn load_progress(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = fs::read_to_string(path)?;
+        for file in text.lines().filter_map(|i| i.strip_prefix("completed ")) {
+            match self.files.iter().position(|i| i == file) {
+                Some(index) => self.completed[index] = true,
+                // progress from before file names were saved has level names, which only
+                // count if they pick out one level
+                None => {
+                    let mut named = self.levels.iter().enumerate().filter(|i| !file.is_empty() && i.1.name == file);
+                    if let (Some((index, _)), None) = (named.next(), named.next()) { self.completed[index] = true }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use crate::{snake::*, bot::Difficulty, autopilot::Autopilot};
+// anything that can steer a snake: the bots, the autopilot, or a closure. it gets the
+// whole board to look at but can't change it, and picks a direction every tick.
+pub trait Controller {
+    // the direction snake `index` should head next.
+    fn pick(&mut self, board: &Board, index: usize) -> Direction;
+}
+impl Controller for Difficulty {
+    fn pick(&mut self, board: &Board, index: usize) -> Direction {
+        Difficulty::pick(self, board, index)
+    }
+}
+impl Controller for Autopilot {
+    fn pick(&mut self, board: &Board, index: usize) -> Direction {
+        Autopilot::pick(self, board, index)
+    }
+}
+impl<F: FnMut(&Board, usize) -> Direction> Controller for F {
+    fn pick(&mut self, board: &Board, index: usize) -> Direction {
+        self(board, index)
+    }
+}
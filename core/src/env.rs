@@ -0,0 +1,154 @@
+use crate::{snake::*, level::*, vector::*, bot::Difficulty};
+// the rules as a training environment: reset to a seed, then step player one's snake
+// one tick at a time and get back what it can see, how well that went and whether the
+// game's over. any other snakes on the level are played by their bots, or the easy one.
+pub struct Env {
+    pub level: Level,
+    pub rewards: Rewards,
+    // games are cut off after this many ticks so one going round in circles ends.
+    pub max_ticks: usize,
+    pub board: Board
+}
+// what each thing that can happen in a tick is worth. they add up if more than one
+// happens at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rewards {
+    pub apple: f32,
+    pub death: f32,
+    pub win: f32,
+    // every tick, negative to hurry it along.
+    pub tick: f32,
+    // for each cell the head gets closer to the apple, taken off for each one further.
+    pub closer: f32
+}
+impl Default for Rewards {
+    fn default() -> Self {
+        Self { apple: 1.0, death: -1.0, win: 10.0, tick: 0.0, closer: 0.0 }
+    }
+}
+// the board as player one sees it. each cell is one of the constants below, by x, then
+// y, then z.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub cells: Vec<u8>,
+    pub head: IVec3,
+    pub apple: IVec3
+}
+impl Observation {
+    pub const EMPTY: u8 = 0;
+    pub const WALL: u8 = 1;
+    // player one's own body, head included.
+    pub const SNAKE: u8 = 2;
+    pub const OTHER: u8 = 3;
+    // an extra apple, plus its kind's place in `AppleKind::ALL`. the usual one is `apple`.
+    pub const EXTRA: u8 = 4;
+    // the cells then the head and apple coordinates, all in one flat list.
+    pub fn to_vec(&self) -> Vec<f32> {
+        let pos = |i: IVec3| [i.x, i.y, i.z].map(|i| i as f32);
+        self.cells.iter().map(|i| *i as f32).chain(pos(self.head)).chain(pos(self.apple)).collect()
+    }
+}
+// how the game stands after a step, beyond the reward.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Info {
+    pub ticks: usize,
+    pub length: usize,
+    pub apples: usize,
+    pub score: u64,
+    pub state: State,
+    pub death: Option<Death>,
+    // the game hit `max_ticks` rather than ending.
+    pub truncated: bool
+}
+impl Env {
+    pub fn new(level: Level) -> Self {
+        let cells = level.width * level.depth * level.height;
+        let board = level.board(0);
+        Self { level, rewards: Rewards::default(), max_ticks: cells * cells + 1000, board }
+    }
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.board = self.level.board(seed);
+        self.observe()
+    }
+    // plays a tick with player one heading in `direction`. a finished game stays as it
+    // is until the next reset.
+    pub fn step(&mut self, direction: Direction) -> (Observation, f32, bool, Info) {
+        let board = &mut self.board;
+        if board.state != State::Alive {
+            let info = self.info();
+            return (self.observe(), 0.0, true, info)
+        }
+        let (apples, distance) = (board.snakes[0].apples, board.snakes[0].pos.manhattan(board.apple_pos));
+        board.snakes[0].direction = direction;
+        for (i, spawn) in self.level.snakes.iter().enumerate().skip(1) {
+            if board.snakes[i].alive { board.snakes[i].direction = spawn.bot.unwrap_or(Difficulty::Easy).pick(board, i) }
+        }
+        board.update();
+
+        let snake = &board.snakes[0];
+        let rewards = self.rewards;
+        let mut reward = rewards.tick;
+        if snake.apples > apples { reward += rewards.apple }
+        else if snake.alive { reward += rewards.closer * (distance - snake.pos.manhattan(board.apple_pos)) as f32 }
+        if !snake.alive { reward += rewards.death }
+        if board.state == State::Win && board.winner == Some(0) { reward += rewards.win }
+        let info = self.info();
+        let done = info.state != State::Alive || info.truncated || !self.board.snakes[0].alive;
+        (self.observe(), reward, done, info)
+    }
+    pub fn observe(&self) -> Observation {
+        let board = &self.board;
+        let size = board.board_size();
+        let own = &board.snakes[0];
+        let mut cells = Vec::with_capacity((size.x * size.y * size.z) as usize);
+        for z in 0..size.z { for y in 0..size.y { for x in 0..size.x {
+            let pos = ivec3(x, y, z);
+            cells.push(match (board.point(pos), board.extras.iter().find(|i| i.pos == pos)) {
+                (_, Some(apple)) => Observation::EXTRA + apple.kind as u8,
+                (Some(Point::Wall), _) => Observation::WALL,
+                (Some(Point::Snake), _) if own.alive && own.body.contains(&pos) => Observation::SNAKE,
+                (Some(Point::Snake), _) => Observation::OTHER,
+                _ => Observation::EMPTY
+            });
+        } } }
+        Observation { cells, head: own.pos, apple: board.apple_pos }
+    }
+    pub fn info(&self) -> Info {
+        let snake = &self.board.snakes[0];
+        Info {
+            ticks: self.board.ticks, length: snake.body.len(), apples: snake.apples,
+            score: snake.score.points, state: self.board.state, death: snake.death,
+            truncated: self.board.ticks >= self.max_ticks
+        }
+    }
+}
+// several environments stepped together. one that finishes starts again straight away
+// on its next seed, so every step gives an observation to act on.
+pub struct VecEnv {
+    pub envs: Vec<Env>,
+    seeds: Vec<u64>
+}
+impl VecEnv {
+    pub fn new(level: Level, count: usize) -> Self {
+        Self { envs: (0..count).map(|_| Env::new(level.clone())).collect(), seeds: vec![0; count] }
+    }
+    // environment `i` starts on `seed + i`, and each goes up by the count every game.
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.seeds = (0..self.envs.len() as u64).map(|i| seed.wrapping_add(i)).collect();
+        self.envs.iter_mut().zip(&self.seeds).map(|(env, seed)| env.reset(*seed)).collect()
+    }
+    // a direction for each environment. the observation of one that just finished is
+    // of its next game, its info is still of the game that ended.
+    pub fn step(&mut self, directions: &[Direction]) -> Vec<(Observation, f32, bool, Info)> {
+        assert_eq!(directions.len(), self.envs.len(), "every environment needs a direction");
+        let count = self.envs.len() as u64;
+        self.envs.iter_mut().zip(&mut self.seeds).zip(directions).map(|((env, seed), direction)| {
+            let (mut observation, reward, done, info) = env.step(*direction);
+            if done {
+                *seed = seed.wrapping_add(count);
+                observation = env.reset(*seed);
+            }
+            (observation, reward, done, info)
+        }).collect()
+    }
+}
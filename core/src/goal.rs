@@ -0,0 +1,61 @@
+use crate::vector::*;
+// what has to happen for a game to be won. filling the board always wins since
+// there is nowhere left to go, `Perfect` just doesn't stop any earlier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    Perfect,
+    // the snake is at least this long.
+    Length(usize),
+    Apples(usize),
+    // ticks without dying.
+    Survive(usize),
+    // the head enters this cell.
+    Reach(IVec3),
+    Score(u64)
+}
+impl Goal {
+    // in the same order as `index`, also how they are written in levels.
+    pub const KINDS: [&'static str; 6] = ["perfect", "length", "apples", "survive", "reach", "score"];
+    pub fn index(&self) -> usize {
+        match self {
+            Goal::Perfect    => 0,
+            Goal::Length(_)  => 1,
+            Goal::Apples(_)  => 2,
+            Goal::Survive(_) => 3,
+            Goal::Reach(_)   => 4,
+            Goal::Score(_)   => 5
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        Self::KINDS[self.index()]
+    }
+    // reads the arguments of a `goal` line, like `apples 10` or `reach 3 3 0`.
+    // a lone number is a length, which is how older levels wrote it.
+    pub fn parse(args: &[&str]) -> Result<Self, String> {
+        let kind = *args.first().ok_or("goal is missing a kind")?;
+        let number = |i: usize| -> Result<u64, String> {
+            let arg = args.get(i).ok_or_else(|| format!("{kind} goal is missing a value"))?;
+            arg.parse().map_err(|_| format!("{arg} is not a number"))
+        };
+        Ok(match kind {
+            "perfect" => Goal::Perfect,
+            "length"  => Goal::Length(number(1)? as usize),
+            "apples"  => Goal::Apples(number(1)? as usize),
+            "survive" => Goal::Survive(number(1)? as usize),
+            "reach"   => Goal::Reach(ivec3(number(1)? as i32, number(2)? as i32, number(3)? as i32)),
+            "score"   => Goal::Score(number(1)?),
+            _ => match kind.parse() {
+                Ok(length) => Goal::Length(length),
+                Err(_) => return Err(format!("{kind} is not a goal, use one of {}", Self::KINDS.join(", ")))
+            }
+        })
+    }
+    pub fn to_text(&self) -> String {
+        match self {
+            Goal::Perfect => "perfect".into(),
+            Goal::Length(i) | Goal::Apples(i) | Goal::Survive(i) => format!("{} {i}", self.name()),
+            Goal::Reach(IVec3 { x, y, z }) => format!("reach {x} {y} {z}"),
+            Goal::Score(i) => format!("score {i}")
+        }
+    }
+}
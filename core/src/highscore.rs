@@ -0,0 +1,102 @@
+use crate::{level::*, apple::*};
+use std::{collections::BTreeMap, fs, io, path::Path};
+// the best scores for each kind of game, so a tiny fast board isn't compared with a
+// huge slow one. saved as text, one entry per line:
+//
+//   key <tab> points <tab> date <tab> seed <tab> replay file
+pub const TABLE_SIZE: usize = 10;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreEntry {
+    pub points: u64,
+    // year-month-day
+    pub date: String,
+    pub seed: u64,
+    // the replay of the game, empty if it wasn't saved.
+    pub replay: String
+}
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HighScores {
+    // best first
+    pub tables: BTreeMap<String, Vec<ScoreEntry>>
+}
+// which table a game goes in: board size, speed, mode (a level's name, or the goal
+// on an empty board), wrapped axes, whether there are walls and any extra apples. games
+// without extras leave them out, keeping the keys from before there were any.
+pub fn score_key(level: &Level, speed: i8) -> String {
+    let wrap: String = "xyz".chars().zip(level.wrap).filter(|i| i.1).map(|i| i.0).collect();
+    let mode = match level.name.as_str() {
+        "" => format!("free {}", level.goal.to_text()),
+        name => name.to_string()
+    };
+    let walls = if level.walls.is_empty() { "open" } else { "walls" };
+    let wrap = if wrap.is_empty() { "none".to_string() } else { wrap };
+    let mut key = format!("{}x{}x{} speed {speed} wrap {wrap} {walls} {mode}", level.width, level.depth, level.height);
+    if level.extra_count > 0 {
+        let kinds = match level.extra_kinds.as_slice() {
+            [] => AppleKind::Normal.name().to_string(),
+            kinds => kinds.iter().map(|i| i.name()).collect::<Vec<_>>().join(",")
+        };
+        key += &format!(" extras {} {kinds}", level.extra_count);
+    }
+    key
+}
+impl HighScores {
+    pub fn table(&self, key: &str) -> &[ScoreEntry] {
+        self.tables.get(key).map_or(&[], |i| i.as_slice())
+    }
+    pub fn best(&self, key: &str) -> Option<&ScoreEntry> {
+        self.table(key).first()
+    }
+    // adds a score, returning its place (0 is the best) if it made the table.
+    pub fn insert(&mut self, key: &str, entry: ScoreEntry) -> Option<usize> {
+        if entry.points == 0 { return None }
+        let table = self.tables.entry(key.to_string()).or_default();
+        // ties go to the older score
+        let rank = table.iter().position(|i| i.points < entry.points).unwrap_or(table.len());
+        if rank >= TABLE_SIZE { return None }
+        table.insert(rank, entry);
+        table.truncate(TABLE_SIZE);
+        Some(rank)
+    }
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (key, table) in &self.tables {
+            for i in table {
+                text += &format!("{key}\t{}\t{}\t{}\t{}\n", i.points, i.date, i.seed, i.replay);
+            }
+        }
+        text
+    }
+    // lines that can't be read are skipped rather than losing every other score.
+    pub fn parse(text: &str) -> Self {
+        let mut scores = Self::default();
+        for line in text.lines() {
+            let [key, points, date, seed, replay] = line.split('\t').collect::<Vec<_>>()[..] else { continue };
+            let (Ok(points), Ok(seed)) = (points.parse(), seed.parse()) else { continue };
+            let entry = ScoreEntry { points, date: date.to_string(), seed, replay: replay.to_string() };
+            scores.insert(key, entry);
+        }
+        scores
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() { fs::create_dir_all(dir)? }
+        fs::write(path, self.to_text())
+    }
+}
+// seconds since 1970 as a year-month-day date, using the days to civil algorithm from
+// http://howardhinnant.github.io/date_algorithms.html
+pub fn date(unix_secs: u64) -> String {
+    let days = (unix_secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{year:04}-{month:02}-{day:02}")
+}
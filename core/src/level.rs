@@ -0,0 +1,290 @@
+use crate::{snake::*, vector::*, goal::*, apple::*, bot::Difficulty};
+use std::{collections::HashSet, fmt, fs, path::Path};
+// levels are plain text, one setting per line. `//` starts a comment.
+//
+//   name Pillars
+//   size 6 6 4              width depth height
+//   wrap xy                 axes that loop round, any of x, y and z
+//   snake 0 0 0 forward 3   head position, direction and length. more snake lines
+//                           add more players, up to 4. easy, medium or hard
+//                           after the length makes it a computer opponent
+//   versus score            with more than one snake, last (the last one left wins,
+//                           the default) or score (the most points wins)
+//   apple 3 3 0             apples are used in order before random ones
+//   extras 2 golden timed   apples out as well as the usual one, and the kinds they
+//                           can be, any of normal, golden, shrinking, speed and
+//                           timed. leave the kinds out for normal ones
+//   goal apples 10          what wins, leave out to fill the board. one of
+//                           perfect, length N, apples N, survive TICKS,
+//                           reach X Y Z or score N
+//   speed 2                 0 (fastest) to 6
+//   layer 0                 then `depth` rows of `width` cells, `#` is a wall
+//   ..#...
+//
+// layer rows start at the back of the board (the largest y) so the text reads
+// like a top down map with forward pointing up the page.
+// the most snakes a level can have, one for each colour and frame the game has.
+pub const MAX_PLAYERS: usize = 4;
+// where a snake starts, `length` long with its head at `pos` facing `direction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spawn {
+    pub pos: IVec3,
+    pub direction: Direction,
+    pub length: usize,
+    // how well the computer plays it, none if a player does.
+    pub bot: Option<Difficulty>
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub name: String,
+    pub width: usize,
+    pub depth: usize,
+    pub height: usize,
+    pub wrap: [bool; 3],
+    pub walls: Vec<IVec3>,
+    // the first is player one.
+    pub snakes: Vec<Spawn>,
+    pub versus: Versus,
+    pub apples: Vec<IVec3>,
+    pub extra_count: usize,
+    pub extra_kinds: Vec<AppleKind>,
+    pub goal: Goal,
+    pub speed: Option<i8>
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelError {
+    // 0 when the error isn't about a particular line.
+    pub line: usize,
+    pub message: String
+}
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "{}", self.message),
+            line => write!(f, "line {line}: {}", self.message)
+        }
+    }
+}
+impl std::error::Error for LevelError {}
+fn error(line: usize, message: impl Into<String>) -> LevelError {
+    LevelError { line, message: message.into() }
+}
+impl Level {
+    // the same empty board `Board::new` makes.
+    pub fn new(width: usize, depth: usize, height: usize) -> Self {
+        Self {
+            name: String::new(), width, depth, height, wrap: [false; 3], walls: vec![],
+            snakes: vec![Spawn { pos: IVec3::ZERO, direction: Direction::Forward, length: 1, bot: None }],
+            versus: Versus::default(), apples: vec![], extra_count: 0, extra_kinds: vec![],
+            goal: Goal::Perfect, speed: None
+        }
+    }
+    // one snake for each player in the corners of the bottom layer, facing along the
+    // depth like the single snake on an empty board.
+    pub fn set_players(&mut self, players: usize) {
+        let (x, y) = (self.width as i32 - 1, self.depth as i32 - 1);
+        let corners = [
+            (ivec3(0, 0, 0), Direction::Forward), (ivec3(x, y, 0), Direction::Back),
+            (ivec3(x, 0, 0), Direction::Forward), (ivec3(0, y, 0), Direction::Back)
+        ];
+        self.snakes = corners[..players.clamp(1, MAX_PLAYERS)].iter()
+            .map(|(pos, direction)| Spawn { pos: *pos, direction: *direction, length: 1, bot: None })
+            .collect();
+    }
+    // adds computer snakes after the players' in the corners left over.
+    pub fn set_opponents(&mut self, opponents: usize, difficulty: Difficulty) {
+        let players = self.snakes.iter().filter(|i| i.bot.is_none()).count();
+        self.set_players(players + opponents);
+        for i in &mut self.snakes[players..] { i.bot = Some(difficulty) }
+    }
+    pub fn board(&self, seed: u64) -> Board {
+        self.set_up(Board::new(self.width, self.depth, self.height, seed))
+    }
+    // lays the level out on a board fresh from `Board::new`, with any rules the apples
+    // depend on already set.
+    pub(crate) fn set_up(&self, mut board: Board) -> Board {
+        board.wrap = self.wrap;
+        board.goal = self.goal;
+        board.versus = self.versus;
+        if let Some(speed) = self.speed { board.speed = speed }
+        for (i, spawn) in self.snakes.iter().enumerate() {
+            board.set_snake(i, spawn.pos, spawn.direction, spawn.length);
+        }
+        for i in &self.walls { board.add_wall(*i); }
+        board.queue_apples(&self.apples);
+        board.set_extras(self.extra_count, &self.extra_kinds);
+        board
+    }
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LevelError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|i| error(0, format!("couldn't read {}: {i}", path.display())))?;
+        Self::parse(&text)
+    }
+    pub fn parse(text: &str) -> Result<Self, LevelError> {
+        let mut level = Self::new(0, 0, 0);
+        let (mut snakes, mut layers) = (vec![], HashSet::new());
+        let mut lines = text.lines().enumerate().map(|(i, line)| {
+            (i + 1, line.split("//").next().unwrap().trim())
+        }).filter(|(_, line)| !line.is_empty());
+
+        while let Some((line, text)) = lines.next() {
+            let (key, args) = text.split_once(' ').unwrap_or((text, ""));
+            let args: Vec<&str> = args.split_whitespace().collect();
+            let number = |i: usize| -> Result<usize, LevelError> {
+                let arg = args.get(i).ok_or_else(|| error(line, format!("{key} is missing a value")))?;
+                arg.parse().map_err(|_| error(line, format!("{arg} is not a number")))
+            };
+            let pos = |i: usize| -> Result<IVec3, LevelError> {
+                Ok(ivec3(number(i)? as i32, number(i + 1)? as i32, number(i + 2)? as i32))
+            };
+            match key {
+                "name" => level.name = args.join(" "),
+                "size" => {
+                    (level.width, level.depth, level.height) = (number(0)?, number(1)?, number(2)?);
+                    if [level.width, level.depth, level.height].iter().any(|i| !(2..=255).contains(i)) {
+                        return Err(error(line, "each side of the board must be from 2 to 255"))
+                    }
+                },
+                "wrap" => for i in args.concat().chars() {
+                    match i {
+                        'x' => level.wrap[0] = true,
+                        'y' => level.wrap[1] = true,
+                        'z' => level.wrap[2] = true,
+                        _ => return Err(error(line, format!("{i} is not an axis, use x, y or z")))
+                    }
+                },
+                "snake" => {
+                    if snakes.len() == MAX_PLAYERS { return Err(error(line, format!("there can be at most {MAX_PLAYERS} snakes"))) }
+                    let direction = args.get(3).ok_or_else(|| error(line, "snake is missing a direction"))?;
+                    let direction = parse_direction(direction).ok_or_else(|| error(line, format!("{direction} is not a direction")))?;
+                    let length = if args.len() > 4 { number(4)? } else { 1 };
+                    if length == 0 { return Err(error(line, "the snake needs a length of at least 1")) }
+                    let bot = match args.get(5) {
+                        Some(arg) => Some(Difficulty::parse(arg).ok_or_else(|| error(line, format!("{arg} is not easy, medium or hard")))?),
+                        None => None
+                    };
+                    snakes.push(Spawn { pos: pos(0)?, direction, length, bot });
+                },
+                "versus" => {
+                    let arg = args.first().copied().unwrap_or_default();
+                    level.versus = Versus::parse(arg).ok_or_else(|| error(line, format!("versus must be last or score, got {arg}")))?;
+                },
+                "apple" => level.apples.push(pos(0)?),
+                "extras" => {
+                    level.extra_count = number(0)?;
+                    if level.extra_count > MAX_EXTRAS { return Err(error(line, format!("there can be at most {MAX_EXTRAS} extra apples"))) }
+                    level.extra_kinds = args[1..].iter()
+                        .map(|i| AppleKind::parse(i).ok_or_else(|| error(line, format!("{i} is not a kind of apple"))))
+                        .collect::<Result<_, _>>()?;
+                },
+                "goal" => level.goal = Goal::parse(&args).map_err(|i| error(line, i))?,
+                "speed" => match number(0)? {
+                    i @ 0..=6 => level.speed = Some(i as i8),
+                    _ => return Err(error(line, "speed must be from 0 to 6"))
+                },
+                "layer" => {
+                    if level.width == 0 { return Err(error(line, "size has to come before any layers")) }
+                    let z = number(0)?;
+                    if z >= level.height { return Err(error(line, format!("layer {z} is above the board"))) }
+                    if !layers.insert(z) { return Err(error(line, format!("layer {z} is already set"))) }
+                    for row in 0..level.depth {
+                        let (line, text) = lines.next()
+                            .ok_or_else(|| error(line, format!("layer {z} needs {} rows", level.depth)))?;
+                        if text.chars().count() != level.width {
+                            return Err(error(line, format!("rows need to be {} cells wide", level.width)))
+                        }
+                        let y = level.depth - 1 - row;
+                        for (x, cell) in text.chars().enumerate() {
+                            match cell {
+                                '#' => level.walls.push(ivec3(x as i32, y as i32, z as i32)),
+                                '.' => (),
+                                _ => return Err(error(line, format!("{cell} is not a cell, use # or .")))
+                            }
+                        }
+                    }
+                },
+                _ => return Err(error(line, format!("unknown setting {key}")))
+            }
+        }
+        if !snakes.is_empty() { level.snakes = snakes }
+        level.validate()?;
+        Ok(level)
+    }
+    // fails if the level can't be laid out on a board as it is, which parsing checks already.
+    pub fn validate(&self) -> Result<(), LevelError> {
+        if self.width == 0 { return Err(error(0, "level has no size")) }
+        if self.snakes.len() > MAX_PLAYERS { return Err(error(0, format!("there can be at most {MAX_PLAYERS} snakes"))) }
+        let size = ivec3(self.width as i32, self.depth as i32, self.height as i32);
+        let inside = |i: IVec3| (0..size.x).contains(&i.x) && (0..size.y).contains(&i.y) && (0..size.z).contains(&i.z);
+        let mut walls = HashSet::new();
+        for i in &self.walls {
+            if !inside(*i) { return Err(error(0, format!("wall at {} {} {} is off the board", i.x, i.y, i.z))) }
+            if !walls.insert(*i) { return Err(error(0, format!("wall at {} {} {} is there twice", i.x, i.y, i.z))) }
+        }
+        let mut snakes = HashSet::new();
+        for spawn in &self.snakes {
+            for i in 0..spawn.length as i32 {
+                let pos = spawn.pos - spawn.direction.dir().scale(i);
+                if !inside(pos) { return Err(error(0, "the snake doesn't fit on the board")) }
+                if walls.contains(&pos) { return Err(error(0, "the snake starts inside a wall")) }
+                if !snakes.insert(pos) { return Err(error(0, "the snakes start on top of each other")) }
+            }
+        }
+        if let Goal::Reach(i) = self.goal {
+            if !inside(i) || walls.contains(&i) { return Err(error(0, "the goal cell is off the board or in a wall")) }
+        }
+        if let Some(i) = self.apples.iter().find(|i| !inside(**i) || walls.contains(i)) {
+            return Err(error(0, format!("apple at {} {} {} is off the board or in a wall", i.x, i.y, i.z)))
+        }
+        Ok(())
+    }
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if !self.name.is_empty() { text += &format!("name {}\n", self.name) }
+        text += &format!("size {} {} {}\n", self.width, self.depth, self.height);
+        if self.wrap.contains(&true) {
+            let axes: String = "xyz".chars().zip(self.wrap).filter(|i| i.1).map(|i| i.0).collect();
+            text += &format!("wrap {axes}\n");
+        }
+        for Spawn { pos: IVec3 { x, y, z }, direction, length, bot } in &self.snakes {
+            text += &format!("snake {x} {y} {z} {} {length}", direction_name(*direction));
+            if let Some(bot) = bot { text += &format!(" {}", bot.name()) }
+            text.push('\n');
+        }
+        if self.versus != Versus::default() { text += &format!("versus {}\n", self.versus.name()) }
+        for IVec3 { x, y, z } in &self.apples { text += &format!("apple {x} {y} {z}\n") }
+        if self.extra_count > 0 {
+            text += &format!("extras {}", self.extra_count);
+            for i in &self.extra_kinds { text += &format!(" {}", i.name()) }
+            text.push('\n');
+        }
+        if self.goal != Goal::Perfect { text += &format!("goal {}\n", self.goal.to_text()) }
+        if let Some(speed) = self.speed { text += &format!("speed {speed}\n") }
+        let walls: HashSet<IVec3> = self.walls.iter().copied().collect();
+        for z in 0..self.height as i32 {
+            if !self.walls.iter().any(|i| i.z == z) { continue }
+            text += &format!("layer {z}\n");
+            for y in (0..self.depth as i32).rev() {
+                for x in 0..self.width as i32 {
+                    text.push(if walls.contains(&ivec3(x, y, z)) { '#' } else { '.' });
+                }
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+fn parse_direction(text: &str) -> Option<Direction> {
+    Direction::ALL.into_iter().find(|i| direction_name(*i) == text)
+}
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Forward => "forward",
+        Direction::Back    => "back",
+        Direction::Left    => "left",
+        Direction::Right   => "right",
+        Direction::Up      => "up",
+        Direction::Down    => "down"
+    }
+}
@@ -0,0 +1,31 @@
+// headless rules for snake 3d. nothing in here should know about windows, gl or audio
+// so bots, servers and tests can link it without pulling in the engine.
+mod vector;
+mod snake;
+mod replay;
+mod level;
+mod campaign;
+mod goal;
+mod score;
+mod apple;
+mod highscore;
+mod lockstep;
+mod autopilot;
+mod controller;
+mod batch;
+mod env;
+pub mod bot;
+pub use vector::*;
+pub use snake::*;
+pub use replay::*;
+pub use level::*;
+pub use campaign::*;
+pub use goal::*;
+pub use score::*;
+pub use apple::*;
+pub use highscore::*;
+pub use lockstep::*;
+pub use autopilot::*;
+pub use controller::*;
+pub use batch::*;
+pub use env::*;
@@ -0,0 +1,185 @@
+use crate::{snake::*, replay::*};
+use std::{collections::BTreeMap, io, net::SocketAddr};
+// online games run the same board on every peer in lockstep. only the direction each
+// player picks for each tick goes over the network, and a tick is only played once
+// every player's direction for it is in. directions are picked `delay` ticks before
+// they're played so they have time to arrive.
+//
+// packets start with "S3DN", a version and a kind:
+//   join   a client asking the host for a place
+//   start  the host's reply: the client's player, the delay, every peer's address as
+//          text (the host's is empty) then the game as a replay with no inputs
+//   input  a player, a tick (le u32), how many directions follow from that tick on,
+//          one per byte, then the tick (le u32, u32::MAX for none) and hash (le u64)
+//          of the sender's latest board, to catch boards that drift apart
+const MAGIC: &[u8; 4] = b"S3DN";
+const VERSION: u8 = 2;
+// how many of a player's latest directions go in every input packet, so lost packets
+// are made up for by the next ones.
+pub const WINDOW: usize = 32;
+// anything longer could outrun the window.
+pub const MAX_DELAY: usize = 10;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Join,
+    Start { player: usize, delay: usize, peers: Vec<Option<SocketAddr>>, game: Replay },
+    Input { player: usize, first_tick: usize, directions: Vec<Direction>, hash: Option<(usize, u64)> }
+}
+impl Packet {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        match self {
+            Packet::Join => bytes.push(0),
+            Packet::Start { player, delay, peers, game } => {
+                bytes.extend([1, *player as u8, *delay as u8, peers.len() as u8]);
+                for i in peers {
+                    let address = i.map(|i| i.to_string()).unwrap_or_default();
+                    bytes.push(address.len() as u8);
+                    bytes.extend(address.bytes());
+                }
+                bytes.extend(game.to_bytes());
+            },
+            Packet::Input { player, first_tick, directions, hash } => {
+                bytes.extend([2, *player as u8]);
+                bytes.extend((*first_tick as u32).to_le_bytes());
+                bytes.push(directions.len() as u8);
+                bytes.extend(directions.iter().map(|i| *i as u8));
+                let (tick, hash) = hash.map_or((u32::MAX, 0), |(tick, hash)| (tick as u32, hash));
+                bytes.extend(tick.to_le_bytes());
+                bytes.extend(hash.to_le_bytes());
+            }
+        }
+        bytes
+    }
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<Self> {
+        let bytes = &mut bytes;
+        if take(bytes, 4).ok() != Some(MAGIC) { return Err(invalid("not a snake 3d packet")) }
+        if take(bytes, 1)?[0] != VERSION { return Err(invalid("unsupported packet version")) }
+        match take(bytes, 1)?[0] {
+            0 => Ok(Packet::Join),
+            1 => {
+                let &[player, delay, count] = take(bytes, 3)? else { unreachable!() };
+                let mut peers = vec![];
+                for _ in 0..count {
+                    let len = take(bytes, 1)?[0] as usize;
+                    let address = std::str::from_utf8(take(bytes, len)?).map_err(|_| invalid("peer address isn't text"))?;
+                    peers.push(match address {
+                        "" => None,
+                        address => Some(address.parse().map_err(|_| invalid("bad peer address"))?)
+                    });
+                }
+                let game = Replay::from_bytes(bytes)?;
+                Ok(Packet::Start { player: player as usize, delay: delay as usize, peers, game })
+            },
+            2 => {
+                let player = take(bytes, 1)?[0] as usize;
+                let first_tick = take_u32(bytes)?;
+                let count = take(bytes, 1)?[0] as usize;
+                let directions = take(bytes, count)?.iter()
+                    .map(|i| Direction::ALL.get(*i as usize).copied().ok_or_else(|| invalid("bad direction in packet")))
+                    .collect::<io::Result<_>>()?;
+                let tick = take_u32(bytes)?;
+                let hash = u64::from_le_bytes(take(bytes, 8)?.try_into().unwrap());
+                let hash = (tick != u32::MAX as usize).then_some((tick, hash));
+                Ok(Packet::Input { player, first_tick, directions, hash })
+            },
+            _ => Err(invalid("unknown packet"))
+        }
+    }
+}
+// one peer's side of a game: the directions in so far and the hashes to check.
+pub struct Lockstep {
+    pub player: usize,
+    pub players: usize,
+    pub delay: usize,
+    // the next tick to play.
+    pub tick: usize,
+    // every player's direction for the ticks not played yet.
+    inputs: BTreeMap<usize, Vec<Option<Direction>>>,
+    // this player's direction for every tick so far, kept for resending.
+    local: Vec<Direction>,
+    // recent board hashes by tick, and ones from other peers for ticks not played yet.
+    hashes: BTreeMap<usize, u64>,
+    remote_hashes: Vec<(usize, u64)>,
+    // the first tick a peer's board didn't match this one.
+    pub desync: Option<usize>
+}
+impl Lockstep {
+    // nobody has picked anything for the first `delay` ticks, so every snake heads the
+    // way it starts.
+    pub fn new(board: &Board, player: usize, delay: usize) -> Self {
+        let delay = delay.clamp(1, MAX_DELAY);
+        let start: Vec<Option<Direction>> = board.snakes.iter().map(|i| Some(i.direction)).collect();
+        Self {
+            player, players: board.snakes.len(), delay, tick: 0,
+            inputs: (0..delay).map(|i| (i, start.clone())).collect(),
+            local: vec![board.snakes[player].direction; delay],
+            hashes: BTreeMap::new(), remote_hashes: vec![], desync: None
+        }
+    }
+    // the direction this player last picked.
+    pub fn last_local(&self) -> Direction {
+        *self.local.last().unwrap()
+    }
+    // whether this player's direction for the tick `delay` ahead is still to be picked.
+    pub fn needs_local(&self) -> bool {
+        self.local.len() <= self.tick + self.delay
+    }
+    pub fn push_local(&mut self, direction: Direction) {
+        if !self.needs_local() { return }
+        self.set(self.local.len(), self.player, direction);
+        self.local.push(direction);
+    }
+    fn set(&mut self, tick: usize, player: usize, direction: Direction) {
+        if tick < self.tick || player >= self.players { return }
+        self.inputs.entry(tick).or_insert_with(|| vec![None; self.players])[player] = Some(direction);
+    }
+    // takes in another peer's input packet. anything else is ignored.
+    pub fn receive(&mut self, packet: &Packet) {
+        let Packet::Input { player, first_tick, directions, hash } = packet else { return };
+        if *player == self.player { return }
+        for (i, direction) in directions.iter().enumerate() { self.set(first_tick + i, *player, *direction) }
+        // the same hash comes in every packet until the sender plays another tick
+        if let Some(hash) = hash.filter(|i| !self.remote_hashes.contains(i)) {
+            self.remote_hashes.push(hash);
+            self.check_hashes();
+        }
+    }
+    fn check_hashes(&mut self) {
+        self.remote_hashes.retain(|(tick, hash)| match self.hashes.get(tick) {
+            Some(own) => {
+                if own != hash && self.desync.is_none_or(|i| *tick < i) { self.desync = Some(*tick) }
+                false
+            },
+            // keep ones for ticks still to come, forget ones too old to check
+            None => *tick >= self.tick
+        });
+    }
+    // whether every direction for the next tick is in.
+    pub fn ready(&self) -> bool {
+        self.inputs.get(&self.tick).is_some_and(|i| i.iter().all(|i| i.is_some()))
+    }
+    // plays the next tick if it's ready and the game isn't over, returning whether it
+    // did. turns queued on the board are ignored, the directions all come from the inputs.
+    pub fn step(&mut self, board: &mut Board) -> bool {
+        if !self.ready() || board.state != State::Alive { return false }
+        let inputs = self.inputs.remove(&self.tick).unwrap();
+        for (snake, direction) in board.snakes.iter_mut().zip(inputs) {
+            snake.queue.clear();
+            snake.direction = direction.unwrap();
+        }
+        board.update();
+        self.hashes.insert(self.tick, board.hash());
+        if self.tick >= WINDOW * 4 { self.hashes.remove(&(self.tick - WINDOW * 4)); }
+        self.tick += 1;
+        self.check_hashes();
+        true
+    }
+    // what to send the other peers: this player's latest directions and board hash.
+    pub fn packet(&self) -> Packet {
+        let first_tick = self.local.len().saturating_sub(WINDOW);
+        let hash = self.tick.checked_sub(1).and_then(|tick| Some((tick, *self.hashes.get(&tick)?)));
+        Packet::Input { player: self.player, first_tick, directions: self.local[first_tick..].to_vec(), hash }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{snake::*, level::*};
+use std::{fs, io, path::Path};
+// a game is fully described by the level it started on, its seed and the direction
+// each snake was heading on every tick, so that is all a replay stores.
+//
+// file layout: "S3DR", version, seed (le u64), speed, rule flags, level text length
+// (le u32), the level as text, tick count (le u32) then one direction per nibble, a
+// nibble for each of the level's snakes every tick. version 4 files have no flags and
+// are played with the rules they were made with. before version 6 levels only had one
+// snake, and before version 7 apples were placed by scanning, which a flag keeps.
+const MAGIC: &[u8; 4] = b"S3DR";
+const VERSION: u8 = 7;
+const ALLOW_REVERSE: u8 = 1;
+const SCANNED_APPLES: u8 = 2;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub level: Level,
+    pub seed: u64,
+    pub speed: i8,
+    pub allow_reverse: bool,
+    pub scanned_apples: bool,
+    // a direction for each snake, every tick.
+    pub inputs: Vec<Vec<Direction>>
+}
+pub(crate) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+// splits `len` bytes off the front of `bytes`.
+pub(crate) fn take<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < len { return Err(invalid("replay is truncated")) }
+    let (start, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(start)
+}
+pub(crate) fn take_u32(bytes: &mut &[u8]) -> io::Result<usize> {
+    Ok(u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap()) as usize)
+}
+impl Replay {
+    pub fn new(level: Level, seed: u64, speed: i8) -> Self {
+        Self { level, seed, speed, allow_reverse: false, scanned_apples: false, inputs: vec![] }
+    }
+    pub fn board(&self) -> Board {
+        let level = &self.level;
+        let mut board = Board::new(level.width, level.depth, level.height, self.seed);
+        board.scanned_apples = self.scanned_apples;
+        let mut board = level.set_up(board);
+        board.speed = self.speed;
+        board.allow_reverse = self.allow_reverse;
+        board
+    }
+    // pushes the directions the snakes just moved in. call right after `Board::update`.
+    pub fn record(&mut self, board: &Board) {
+        self.inputs.push(board.snakes.iter().map(|i| i.direction).collect());
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend(self.seed.to_le_bytes());
+        bytes.push(self.speed as u8);
+        bytes.push(if self.allow_reverse { ALLOW_REVERSE } else { 0 } | if self.scanned_apples { SCANNED_APPLES } else { 0 });
+        let level = self.level.to_text();
+        bytes.extend((level.len() as u32).to_le_bytes());
+        bytes.extend(level.bytes());
+        bytes.extend((self.inputs.len() as u32).to_le_bytes());
+        let inputs: Vec<Direction> = self.inputs.concat();
+        bytes.extend(inputs.chunks(2).map(|i| {
+            let index = |d: &Direction| Direction::ALL.iter().position(|a| a == d).unwrap() as u8;
+            index(&i[0]) | i.get(1).map_or(0, index) << 4
+        }));
+        bytes
+    }
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<Self> {
+        let bytes = &mut bytes;
+        if take(bytes, 4).ok() != Some(MAGIC) { return Err(invalid("not a snake 3d replay")) }
+        let version = take(bytes, 1)?[0];
+        if !(4..=VERSION).contains(&version) { return Err(invalid("unsupported replay version")) }
+        let seed = u64::from_le_bytes(take(bytes, 8)?.try_into().unwrap());
+        let speed = take(bytes, 1)?[0] as i8;
+        // reversing was always allowed before there was a choice
+        let flags = if version >= 5 { take(bytes, 1)?[0] } else { ALLOW_REVERSE };
+        let allow_reverse = flags & ALLOW_REVERSE != 0;
+        let scanned_apples = version < 7 || flags & SCANNED_APPLES != 0;
+        let level_len = take_u32(bytes)?;
+        let level = std::str::from_utf8(take(bytes, level_len)?).map_err(|_| invalid("replay level isn't text"))?;
+        let level = Level::parse(level).map_err(|i| invalid(&format!("replay level is broken, {i}")))?;
+        let ticks = take_u32(bytes)?;
+        let snakes = level.snakes.len();
+        let data = take(bytes, (ticks * snakes).div_ceil(2))?;
+
+        let mut inputs = Vec::with_capacity(ticks);
+        for tick in 0..ticks {
+            let mut directions = Vec::with_capacity(snakes);
+            for i in tick * snakes..(tick + 1) * snakes {
+                let nibble = data[i / 2] >> (i % 2 * 4) & 0xF;
+                let dir = Direction::ALL.get(nibble as usize).ok_or_else(|| invalid("bad direction in replay"))?;
+                directions.push(*dir);
+            }
+            inputs.push(directions);
+        }
+        Ok(Self { level, seed, speed, allow_reverse, scanned_apples, inputs })
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() { fs::create_dir_all(dir)? }
+        fs::write(path, self.to_bytes())
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+// drives a board through a replay one tick at a time. rewinding rebuilds the board
+// from the seed since the rules can't be run backwards.
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    tick: usize
+}
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> (Self, Board) {
+        let board = replay.board();
+        (Self { replay, tick: 0 }, board)
+    }
+    pub fn tick(&self) -> usize { self.tick }
+    pub fn finished(&self, board: &Board) -> bool {
+        self.tick >= self.replay.inputs.len() || board.state != State::Alive
+    }
+    pub fn step(&mut self, board: &mut Board) {
+        if self.finished(board) { return }
+        for (snake, direction) in board.snakes.iter_mut().zip(&self.replay.inputs[self.tick]) {
+            snake.direction = *direction;
+        }
+        board.update();
+        self.tick += 1;
+    }
+    pub fn seek(&mut self, board: &mut Board, tick: usize) {
+        if tick < self.tick {
+            *board = self.replay.board();
+            self.tick = 0;
+        }
+        while self.tick < tick && !self.finished(board) { self.step(board) }
+    }
+    pub fn rewind(&mut self, board: &mut Board, ticks: usize) {
+        self.seek(board, self.tick.saturating_sub(ticks))
+    }
+}
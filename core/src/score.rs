@@ -0,0 +1,49 @@
+use crate::vector::*;
+// every apple is worth more on faster speeds and bigger boards, and apples eaten soon
+// after the one before build up a combo that multiplies it again.
+pub const APPLE_POINTS: u64 = 10;
+pub const MAX_COMBO: u32 = 4;
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    pub points: u64,
+    // apples in a row that were each eaten in time to keep the combo going.
+    pub combo: u32,
+    pub best_combo: u32,
+    last_apple: Option<usize>
+}
+impl Score {
+    // by speed setting, 0 (fastest) to 6 (only moving on input).
+    pub fn speed_multiplier(speed: i8) -> u64 {
+        match speed {
+            0 => 5,
+            1 => 4,
+            2 => 3,
+            3 | 4 => 2,
+            _ => 1
+        }
+    }
+    // the average side, so a 4x4x4 board doubles points and an 8x8x8 one quadruples them.
+    pub fn size_multiplier(size: IVec3) -> u64 {
+        ((size.x + size.y + size.z) / 6).max(1) as u64
+    }
+    // ticks the next apple has to be eaten within to keep the combo, about the
+    // distance between two random cells.
+    pub fn combo_ticks(size: IVec3) -> usize {
+        ((size.x + size.y + size.z) / 2) as usize
+    }
+    // the tick the last apple was eaten on.
+    pub fn last_apple(&self) -> Option<usize> { self.last_apple }
+    pub fn apple_points(&self, speed: i8, size: IVec3) -> u64 {
+        APPLE_POINTS * Self::speed_multiplier(speed) * Self::size_multiplier(size) * (1 + self.combo as u64)
+    }
+    // call when an apple is eaten on `tick`. returns the points it was worth.
+    pub fn eat(&mut self, tick: usize, speed: i8, size: IVec3) -> u64 {
+        let in_time = self.last_apple.is_some_and(|i| tick - i <= Self::combo_ticks(size));
+        self.combo = if in_time { (self.combo + 1).min(MAX_COMBO) } else { 0 };
+        self.best_combo = self.best_combo.max(self.combo);
+        self.last_apple = Some(tick);
+        let points = self.apple_points(speed, size);
+        self.points += points;
+        points
+    }
+}
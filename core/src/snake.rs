@@ -0,0 +1,532 @@
+use crate::{vector::*, goal::*, score::*, apple::*};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+pub struct Snake{
+    pub pos: IVec3,
+    pub direction: Direction,
+    // tail first, so the last cell is the head.
+    pub body: Vec<IVec3>,
+    // turns waiting for the coming ticks, one is taken each update.
+    pub queue: VecDeque<Direction>,
+    // how many turns can wait at once, at least 1.
+    pub queue_depth: usize,
+    // a dead snake's body stays where it died for drawing but doesn't block anyone.
+    pub alive: bool,
+    // what it ran into, once it's dead.
+    pub death: Option<Death>,
+    // segments left to grow from a golden apple, one a tick.
+    pub growth: usize,
+    pub apples: usize,
+    pub score: Score
+}
+impl Snake {
+    pub fn new(pos: IVec3, direction: Direction) -> Self {
+        Self {
+            pos, direction, body: vec![pos], queue: VecDeque::new(), queue_depth: 3,
+            alive: true, death: None, growth: 0, apples: 0, score: Score::default()
+        }
+    }
+    // queues a turn for a coming tick. pressing the direction that would already be
+    // taken does nothing, and turns past `queue_depth` are dropped.
+    pub fn turn(&mut self, direction: Direction) {
+        let last = self.queue.back().copied().unwrap_or(self.direction);
+        if direction == last || self.queue.len() >= self.queue_depth.max(1) { return }
+        self.queue.push_back(direction);
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction{
+    Forward,
+    Back,
+    Left,
+    Right,
+    Up,
+    Down
+}
+impl Direction{
+    pub const ALL: [Direction; 6] = [
+        Direction::Forward, Direction::Back,
+        Direction::Left, Direction::Right,
+        Direction::Up, Direction::Down
+    ];
+    pub fn dir(&self) -> IVec3 {
+        match self {
+            Direction::Forward =>  IVec3::Y,
+            Direction::Back    => -IVec3::Y,
+            Direction::Left    => -IVec3::X,
+            Direction::Right   =>  IVec3::X,
+            Direction::Up      =>  IVec3::Z,
+            Direction::Down    => -IVec3::Z
+        }
+    }
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Forward => Direction::Back,
+            Direction::Back    => Direction::Forward,
+            Direction::Left    => Direction::Right,
+            Direction::Right   => Direction::Left,
+            Direction::Up      => Direction::Down,
+            Direction::Down    => Direction::Up
+        }
+    }
+}
+// what a snake died of.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Death {
+    // went off a side that doesn't wrap.
+    Edge,
+    Wall,
+    Itself,
+    // ran into another snake's body.
+    Snake,
+    // moved into the same cell as another snake's head.
+    HeadOn
+}
+impl Death {
+    pub const ALL: [Death; 5] = [Death::Edge, Death::Wall, Death::Itself, Death::Snake, Death::HeadOn];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Death::Edge   => "edge",
+            Death::Wall   => "wall",
+            Death::Itself => "itself",
+            Death::Snake  => "snake",
+            Death::HeadOn => "head on"
+        }
+    }
+}
+// who wins when more than one snake is playing. a snake that meets the goal, or the
+// one with most points when the board fills up, wins either way.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Versus {
+    // the game ends as soon as one snake is left.
+    #[default]
+    LastStanding,
+    // the game goes on until every snake is dead, then the most points wins.
+    HighScore
+}
+impl Versus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Versus::LastStanding => "last",
+            Versus::HighScore    => "score"
+        }
+    }
+    pub fn parse(text: &str) -> Option<Self> {
+        [Versus::LastStanding, Versus::HighScore].into_iter().find(|i| i.name() == text)
+    }
+}
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum State {
+    Wait,
+    Alive,
+    Dead,
+    Win
+}
+pub struct Board {
+    // the first snake is player one. there's always at least one.
+    pub snakes: Vec<Snake>,
+    pub apple_pos: IVec3,
+    // apples out as well as the usual one, kept topped up to `extra_count` of them, each
+    // one of `extra_kinds` or a normal one if there are none.
+    pub extras: Vec<Apple>,
+    pub extra_count: usize,
+    pub extra_kinds: Vec<AppleKind>,
+    // ticks left of a speed apple. the game plays faster until it's back to 0.
+    pub haste: usize,
+    // every apple eaten on the last tick, for the sounds.
+    pub eaten: Vec<AppleKind>,
+    // x is width, y is depth and z (up) is height, matching `Direction::dir`.
+    width: usize,
+    depth: usize,
+    height: usize,
+    grid: Vec<Vec<Vec<Point>>>,
+    // every empty cell's index, in no order, and each cell's place in it or `u32::MAX`,
+    // so cells go in and out straight away and apples can be picked from it evenly.
+    empty: Vec<u32>,
+    slots: Vec<u32>,
+    walls: Vec<IVec3>,
+    // apples placed by a level, in reverse order so the next one can be popped.
+    apple_queue: Vec<IVec3>,
+    // chacha is used over StdRng because its output is stable across rand versions,
+    // so a seed will always replay the same apples.
+    rng: ChaCha8Rng,
+    seed: u64,
+    // axes (x, y, z) where leaving one face comes back in the opposite one.
+    pub wrap: [bool; 3],
+    pub goal: Goal,
+    pub versus: Versus,
+    // ticks survived so far, for the goals.
+    pub ticks: usize,
+    // the speed setting the game is played at, only used for scoring.
+    pub speed: i8,
+    // lets a snake longer than 1 turn straight back into its neck.
+    pub allow_reverse: bool,
+    // picks cells for apples the way replays before version 7 were played, going on
+    // from a random cell to the first empty one. cells just past a long snake or wall
+    // come up far more often.
+    pub scanned_apples: bool,
+    // the snake that won, once the game is over. none on a draw or a loss.
+    pub winner: Option<usize>,
+    pub state: State
+}
+impl Board {
+    pub fn board_size(&self) -> IVec3 {
+        ivec3(self.width as i32, self.depth as i32, self.height as i32)
+    }
+    pub fn wrap_pos(&self, pos: IVec3) -> IVec3 {
+        let size = self.board_size();
+        let wrap = |value: i32, size: i32, wrap: bool| if wrap { value.rem_euclid(size) } else { value };
+        ivec3(
+            wrap(pos.x, size.x, self.wrap[0]),
+            wrap(pos.y, size.y, self.wrap[1]),
+            wrap(pos.z, size.z, self.wrap[2])
+        )
+    }
+    // where snake `index`'s head ends up after moving in `direction`. may be off the board.
+    pub fn next_pos(&self, index: usize, direction: Direction) -> IVec3 {
+        self.wrap_pos(self.snakes[index].pos + direction.dir())
+    }
+    // every snake moves at once. tails move out of the way first so snakes can follow
+    // each other, then any head in something, or in another head, dies.
+    pub fn update(&mut self) {
+        self.eaten.clear();
+        for index in 0..self.snakes.len() {
+            let snake = &mut self.snakes[index];
+            if !snake.alive { continue }
+            if let Some(direction) = snake.queue.pop_front() { snake.direction = direction }
+            // turning back on itself is ignored and the snake carries on straight
+            let direction = self.snakes[index].direction;
+            if self.is_reverse(index, direction) && !self.allow_reverse {
+                self.snakes[index].direction = direction.opposite();
+            }
+        }
+        let heads: Vec<Option<IVec3>> = (0..self.snakes.len())
+            .map(|i| self.snakes[i].alive.then(|| self.next_pos(i, self.snakes[i].direction)))
+            .collect();
+        // two heads moving into each other's cells pass through each other once the tails
+        // are out of the way, so swapping places is found first
+        let swapped: Vec<bool> = heads.iter().enumerate().map(|(index, head)| {
+            heads.iter().enumerate().any(|(i, other)| {
+                i != index && *head == Some(self.snakes[i].pos) && *other == Some(self.snakes[index].pos)
+            })
+        }).collect();
+        for (index, head) in heads.iter().enumerate() {
+            let Some(head) = head else { continue };
+            if self.grows_at(*head) { continue }
+            if self.snakes[index].growth > 0 {
+                self.snakes[index].growth -= 1;
+                continue
+            }
+            let pos = self.snakes[index].body.remove(0);
+            self.set_point(pos, Point::Empty);
+        }
+        let deaths: Vec<Option<Death>> = heads.iter().enumerate().map(|(index, head)| {
+            let head = (*head)?;
+            if swapped[index] { return Some(Death::HeadOn) }
+            match self.point(head) {
+                None => Some(Death::Edge),
+                Some(Point::Wall) => Some(Death::Wall),
+                Some(Point::Snake) if self.snakes[index].body.contains(&head) => Some(Death::Itself),
+                Some(Point::Snake) => Some(Death::Snake),
+                Some(Point::Empty) => heads.iter().enumerate()
+                    .any(|(i, other)| i != index && *other == Some(head))
+                    .then_some(Death::HeadOn)
+            }
+        }).collect();
+
+        let mut collected_apple = false;
+        for (index, head) in heads.into_iter().enumerate() {
+            let Some(head) = head else { continue };
+            if let Some(death) = deaths[index] {
+                (self.snakes[index].alive, self.snakes[index].death) = (false, Some(death));
+                for i in self.snakes[index].body.clone() { self.set_point(i, Point::Empty) }
+                continue
+            }
+            self.set_point(head, Point::Snake);
+            let snake = &mut self.snakes[index];
+            snake.body.push(head);
+            snake.pos = head;
+            collected_apple |= head == self.apple_pos;
+        }
+        if self.alive() == 0 && self.snakes.len() == 1 {
+            self.state = State::Dead;
+            return;
+        }
+        self.ticks += 1;
+        let size = self.board_size();
+        for snake in self.snakes.iter_mut().filter(|i| i.alive && i.pos == self.apple_pos) {
+            snake.apples += 1;
+            snake.score.eat(self.ticks, self.speed, size);
+            self.eaten.push(AppleKind::Normal);
+        }
+        self.haste = self.haste.saturating_sub(1);
+        self.eat_extras();
+
+        if self.check_end() { return }
+        // an apple that had nowhere to go gets another try
+        if collected_apple || self.point(self.apple_pos) != Some(Point::Empty) { self.spawn_apple(); }
+        self.spawn_extras();
+    }
+    // whether a snake moving into `pos` grows, leaving its tail where it is.
+    pub fn grows_at(&self, pos: IVec3) -> bool {
+        pos == self.apple_pos || self.extras.iter().any(|i| i.pos == pos && i.kind.grows())
+    }
+    // hands out what the extra apples under a head do, then rots the timed ones.
+    fn eat_extras(&mut self) {
+        let size = self.board_size();
+        let mut i = 0;
+        while i < self.extras.len() {
+            let apple = self.extras[i];
+            let Some(index) = self.snakes.iter().position(|i| i.alive && i.pos == apple.pos) else { i += 1; continue };
+            self.extras.remove(i);
+            self.eaten.push(apple.kind);
+            let snake = &mut self.snakes[index];
+            snake.apples += 1;
+            let points = snake.score.eat(self.ticks, self.speed, size);
+            match apple.kind {
+                AppleKind::Normal => (),
+                AppleKind::Golden => {
+                    snake.score.points += points * (GOLDEN_GROWTH as u64 - 1);
+                    snake.growth += GOLDEN_GROWTH - 1;
+                },
+                AppleKind::Shrinking => {
+                    let cut: Vec<IVec3> = snake.body.drain(..SHRINK.min(snake.body.len() - 1)).collect();
+                    for pos in cut { self.set_point(pos, Point::Empty) }
+                },
+                AppleKind::Speed => self.haste = HASTE_TICKS,
+                AppleKind::Timed => snake.score.points += points
+            }
+        }
+        for ticks in self.extras.iter_mut().filter_map(|i| i.ticks_left.as_mut()) { *ticks -= 1 }
+        self.extras.retain(|i| i.ticks_left != Some(0));
+    }
+    // sets `state` and `winner` if the game is over, returning true if it is.
+    fn check_end(&mut self) -> bool {
+        let best = |snakes: &mut dyn Iterator<Item = usize>| -> Option<usize> {
+            let snakes: Vec<usize> = snakes.collect();
+            let points = snakes.iter().map(|i| self.snakes[*i].score.points).max()?;
+            let mut best = snakes.into_iter().filter(|i| self.snakes[*i].score.points == points);
+            // a tie is a draw
+            match (best.next(), best.next()) {
+                (Some(i), None) => Some(i),
+                _ => None
+            }
+        };
+        let alive = (0..self.snakes.len()).filter(|i| self.snakes[*i].alive);
+        let filled: usize = self.snakes.iter().filter(|i| i.alive).map(|i| i.body.len()).sum();
+        let (over, winner) = if alive.clone().any(|i| self.goal_left(i) == 0) {
+            // whoever has most points if more than one got there on the same tick
+            (true, best(&mut alive.clone().filter(|i| self.goal_left(*i) == 0)))
+        } else if filled >= self.free_cells() {
+            (true, best(&mut alive.clone()))
+        } else {
+            match (self.versus, self.alive()) {
+                (_, 0) => (true, best(&mut (0..self.snakes.len()))),
+                (Versus::LastStanding, 1) if self.snakes.len() > 1 => (true, alive.clone().next()),
+                _ => (false, None)
+            }
+        };
+        if !over { return false }
+        (self.state, self.winner) = match winner {
+            Some(i) => (State::Win, Some(i)),
+            None => (State::Dead, None)
+        };
+        true
+    }
+    // snakes still moving.
+    pub fn alive(&self) -> usize {
+        self.snakes.iter().filter(|i| i.alive).count()
+    }
+    // whether moving in `direction` takes snake `index` back into the segment behind its head.
+    pub fn is_reverse(&self, index: usize, direction: Direction) -> bool {
+        let body = &self.snakes[index].body;
+        body.len() > 1 && self.next_pos(index, direction) == body[body.len() - 2]
+    }
+    pub fn free_cells(&self) -> usize {
+        self.width*self.height*self.depth - self.walls.len()
+    }
+    // how far snake `index` is off the goal, in whatever it counts. 0 once it's met.
+    pub fn goal_left(&self, index: usize) -> u64 {
+        let left = |target: usize, value: usize| target.saturating_sub(value) as u64;
+        let snake = &self.snakes[index];
+        match self.goal {
+            // filling the board is shared
+            Goal::Perfect        => left(self.free_cells(), self.snakes.iter().filter(|i| i.alive).map(|i| i.body.len()).sum()),
+            Goal::Length(length) => left(length, snake.body.len()),
+            Goal::Apples(apples) => left(apples, snake.apples),
+            Goal::Survive(ticks) => left(ticks, self.ticks),
+            Goal::Reach(pos)     => pos.manhattan(snake.pos) as u64,
+            Goal::Score(score)   => score.saturating_sub(snake.score.points)
+        }
+    }
+    pub fn seed(&self) -> u64 { self.seed }
+    // a fingerprint of everything the rules depend on, so two boards that should be in
+    // step can be checked cheaply. fnv-1a so it's the same on every machine and build.
+    pub fn hash(&self) -> u64 {
+        let pos = |i: IVec3| [i.x, i.y, i.z].map(|i| i as u64);
+        let mut values = vec![self.ticks as u64, self.state as u64, self.rng.get_word_pos() as u64];
+        values.extend(pos(self.apple_pos));
+        values.push(self.haste as u64);
+        for apple in &self.extras {
+            values.extend(pos(apple.pos));
+            values.extend([apple.kind as u64, apple.ticks_left.unwrap_or(0) as u64]);
+        }
+        for snake in &self.snakes {
+            values.extend([snake.alive as u64, snake.direction as u64, snake.score.points, snake.apples as u64, snake.body.len() as u64, snake.growth as u64]);
+            values.extend(snake.body.iter().flat_map(|i| pos(*i)));
+        }
+        values.iter().flat_map(|i| i.to_le_bytes())
+            .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+    }
+    pub fn walls(&self) -> &[IVec3] { &self.walls }
+    // turns an empty cell into a wall. fails if it's off the board or taken.
+    pub fn add_wall(&mut self, pos: IVec3) -> bool {
+        if self.point(pos) != Some(Point::Empty) { return false }
+        self.set_point(pos, Point::Wall);
+        self.walls.push(pos);
+        self.extras.retain(|i| i.pos != pos);
+        if pos == self.apple_pos { self.spawn_apple(); }
+        true
+    }
+    // replaces snake `index` with one `length` long ending at `head`, with its body
+    // trailing behind `direction`. an index one past the last snake adds a new one.
+    // fails if any of it would be off the board, in a wall or in another snake.
+    pub fn set_snake(&mut self, index: usize, head: IVec3, direction: Direction, length: usize) -> bool {
+        if index > self.snakes.len() { return false }
+        let body: Vec<IVec3> = (0..length.max(1) as i32).rev().map(|i| head - direction.dir().scale(i)).collect();
+        let own = |pos: &IVec3| self.snakes.get(index).is_some_and(|i| i.body.contains(pos));
+        if body.iter().any(|i| !(self.point(*i) == Some(Point::Empty) || own(i))) { return false }
+        let mut snake = Snake::new(head, direction);
+        if let Some(old) = self.snakes.get_mut(index) {
+            snake.queue_depth = old.queue_depth;
+            for i in std::mem::replace(old, snake).body { self.set_point(i, Point::Empty) }
+        } else {
+            self.snakes.push(snake);
+        }
+        for i in &body { self.set_point(*i, Point::Snake) }
+        self.snakes[index].body = body;
+        if self.point(self.apple_pos) != Some(Point::Empty) { self.spawn_apple(); }
+        true
+    }
+    // the first empty position becomes the apple and the rest are used in order as
+    // apples get eaten, before going back to random ones.
+    pub fn queue_apples(&mut self, apples: &[IVec3]) {
+        self.apple_queue = apples.iter().rev().copied().collect();
+        if !self.apple_queue.is_empty() { self.spawn_apple(); }
+    }
+    // sets how many extra apples are kept out and what kinds they can be, and puts
+    // them out.
+    pub fn set_extras(&mut self, count: usize, kinds: &[AppleKind]) {
+        (self.extra_count, self.extra_kinds) = (count, kinds.to_vec());
+        self.extras.clear();
+        self.spawn_extras();
+    }
+    // moves the apple to the next queued cell or a random empty one and says where it
+    // went. none if there's nowhere, which leaves it where it was.
+    pub fn spawn_apple(&mut self) -> Option<IVec3> {
+        let extras: Vec<IVec3> = self.extras.iter().map(|i| i.pos).collect();
+        while let Some(pos) = self.apple_queue.pop() {
+            if self.point(pos) == Some(Point::Empty) && !extras.contains(&pos) {
+                self.apple_pos = pos;
+                return Some(pos)
+            }
+        }
+        self.apple_pos = self.random_cell(&extras)?;
+        Some(self.apple_pos)
+    }
+    fn spawn_extras(&mut self) {
+        while self.extras.len() < self.extra_count {
+            let mut taken: Vec<IVec3> = self.extras.iter().map(|i| i.pos).collect();
+            taken.push(self.apple_pos);
+            let Some(pos) = self.random_cell(&taken) else { return };
+            let kind = match self.extra_kinds.len() {
+                0 => AppleKind::Normal,
+                len => self.extra_kinds[self.rng.gen_range(0..len)]
+            };
+            let ticks_left = (kind == AppleKind::Timed).then_some(ROT_TICKS);
+            self.extras.push(Apple { pos, kind, ticks_left });
+        }
+    }
+    // an empty cell that isn't `taken`, each as likely as the rest. none if there isn't one.
+    fn random_cell(&mut self, taken: &[IVec3]) -> Option<IVec3> {
+        if self.scanned_apples { return self.scan_cell(taken) }
+        let taken_empty = taken.iter().filter(|i| self.point(**i) == Some(Point::Empty)).count();
+        if self.empty.len() <= taken_empty { return None }
+        // `taken` is only ever a few apples, so picking again is quicker than leaving them out
+        loop {
+            let slot = self.rng.gen_range(0..self.empty.len());
+            let pos = self.cell_pos(self.empty[slot]);
+            if !taken.contains(&pos) { return Some(pos) }
+        }
+    }
+    // an empty cell that isn't `taken`, going on from a random one, for `scanned_apples`.
+    fn scan_cell(&mut self, taken: &[IVec3]) -> Option<IVec3> {
+        let mut i = self.rng.gen::<u64>() as usize;
+        for _ in 0..(self.width*self.height*self.depth) {
+            let z = i % self.height;
+            let y = i / self.height % self.depth;
+            let x = i / (self.height*self.depth) % self.width;
+            let pos = ivec3(x as i32, y as i32, z as i32);
+
+            match &self.grid[x][y][z] {
+                Point::Empty if !taken.contains(&pos) => return Some(pos),
+                _ => i = i.wrapping_add(1)
+            }
+        }
+        None
+    }
+    pub fn new(width: usize, depth: usize, height: usize, seed: u64) -> Self{
+        let mut grid = vec![vec![vec![Point::Empty; height]; depth]; width];
+        grid[0][0][0] = Point::Snake;
+        let cells = width * depth * height;
+        let empty: Vec<u32> = (1..cells as u32).collect();
+        let mut slots = vec![u32::MAX; cells];
+        for (slot, cell) in empty.iter().enumerate() { slots[*cell as usize] = slot as u32 }
+        Self { 
+            snakes: vec![Snake::new(IVec3::ZERO, Direction::Forward)],
+            apple_pos: ivec3(0, 1, 0),
+            extras: vec![], extra_count: 0, extra_kinds: vec![], haste: 0, eaten: vec![],
+            width, depth, height, grid, empty, slots, walls: vec![], apple_queue: vec![],
+            rng: ChaCha8Rng::seed_from_u64(seed), seed,
+            wrap: [false; 3], goal: Goal::Perfect, versus: Versus::default(), ticks: 0,
+            speed: 3, allow_reverse: false, scanned_apples: false, winner: None,
+            state: State::Alive
+        }
+    }
+    pub fn point(&self, index: IVec3) -> Option<Point> {
+        self.grid.get(index.x as usize)
+            .and_then(|i| i.get(index.y as usize))
+            .and_then(|i| i.get(index.z as usize))
+            .copied()
+    }
+    fn point_at(&mut self, index: IVec3) -> Option<&mut Point> {
+        self.grid.get_mut(index.x as usize)
+            .and_then(|i| i.get_mut(index.y as usize))
+            .and_then(|i| i.get_mut(index.z as usize))
+    }
+    // every change to the grid goes through here to keep `empty` in step.
+    fn set_point(&mut self, pos: IVec3, point: Point) {
+        let Some(old) = self.point_at(pos) else { return };
+        let was = std::mem::replace(old, point);
+        let cell = (pos.x as usize + pos.y as usize * self.width + pos.z as usize * self.width * self.depth) as u32;
+        if was != Point::Empty && point == Point::Empty {
+            self.slots[cell as usize] = self.empty.len() as u32;
+            self.empty.push(cell);
+        } else if was == Point::Empty && point != Point::Empty {
+            let slot = std::mem::replace(&mut self.slots[cell as usize], u32::MAX) as usize;
+            self.empty.swap_remove(slot);
+            if let Some(moved) = self.empty.get(slot) { self.slots[*moved as usize] = slot as u32 }
+        }
+    }
+    fn cell_pos(&self, cell: u32) -> IVec3 {
+        let cell = cell as usize;
+        ivec3((cell % self.width) as i32, (cell / self.width % self.depth) as i32, (cell / (self.width * self.depth)) as i32)
+    }
+}
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Point{
+    Empty,
+    Snake,
+    Wall
+}
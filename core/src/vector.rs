@@ -0,0 +1,39 @@
+use std::ops::*;
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct IVec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32
+}
+pub const fn ivec3(x: i32, y: i32, z: i32) -> IVec3 { IVec3 { x, y, z } }
+impl IVec3 {
+    pub const ZERO: Self = ivec3(0, 0, 0);
+    pub const ONE:  Self = ivec3(1, 1, 1);
+    pub const X:    Self = ivec3(1, 0, 0);
+    pub const Y:    Self = ivec3(0, 1, 0);
+    pub const Z:    Self = ivec3(0, 0, 1);
+    pub fn scale(self, scalar: i32) -> Self {
+        ivec3(self.x * scalar, self.y * scalar, self.z * scalar)
+    }
+    pub fn manhattan(self, other: Self) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+}
+impl Add for IVec3 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self { ivec3(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z) }
+}
+impl Sub for IVec3 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self { ivec3(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z) }
+}
+impl Neg for IVec3 {
+    type Output = Self;
+    fn neg(self) -> Self { ivec3(-self.x, -self.y, -self.z) }
+}
+impl AddAssign for IVec3 {
+    fn add_assign(&mut self, rhs: Self) { *self = *self + rhs }
+}
+impl SubAssign for IVec3 {
+    fn sub_assign(&mut self, rhs: Self) { *self = *self - rhs }
+}
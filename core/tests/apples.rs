@@ -0,0 +1,70 @@
+use snake3d_core::*;
+mod common;
+// a snake `length` long heading forward with its head at 0 3 0, an extra apple of `kind`
+// just in front and the usual one out of the way.
+fn board(kind: AppleKind, length: usize) -> Board {
+    let mut board = common::level(&[(ivec3(0, 3, 0), Direction::Forward, length)]).board(0);
+    let ticks_left = (kind == AppleKind::Timed).then_some(ROT_TICKS);
+    board.extras.push(Apple { pos: ivec3(0, 4, 0), kind, ticks_left });
+    board
+}
+#[test]
+fn golden_apples_grow_more() {
+    let mut board = board(AppleKind::Golden, 1);
+    board.update();
+    assert_eq!(board.eaten, [AppleKind::Golden]);
+    let points = Score::default().apple_points(board.speed, board.board_size());
+    assert_eq!(board.snakes[0].score.points, points * GOLDEN_GROWTH as u64);
+    for _ in 0..4 { board.update() }
+    assert_eq!(board.snakes[0].body.len(), GOLDEN_GROWTH);
+    assert!(board.extras.is_empty() && board.eaten.is_empty());
+}
+#[test]
+fn shrinking_apples_cut_the_tail() {
+    let mut board = board(AppleKind::Shrinking, 4);
+    board.update();
+    assert_eq!(board.snakes[0].body, [ivec3(0, 3, 0), ivec3(0, 4, 0)]);
+    assert_eq!(board.point(ivec3(0, 2, 0)), Some(Point::Empty));
+    assert_eq!(board.snakes[0].apples, 1);
+}
+#[test]
+fn speed_apples_wear_off() {
+    let mut board = board(AppleKind::Speed, 1);
+    board.update();
+    assert_eq!(board.haste, HASTE_TICKS);
+    board.update();
+    assert_eq!(board.haste, HASTE_TICKS - 1);
+}
+#[test]
+fn timed_apples_rot() {
+    let mut board = board(AppleKind::Timed, 1);
+    // round and round away from it
+    board.wrap = [true; 3];
+    board.snakes[0].direction = Direction::Right;
+    for _ in 0..ROT_TICKS - 1 { board.update() }
+    assert_eq!(board.extras[0].ticks_left, Some(1));
+    board.update();
+    assert!(board.extras.is_empty());
+}
+#[test]
+fn extras_are_kept_topped_up() {
+    let mut level = Level::new(5, 5, 5);
+    (level.extra_count, level.extra_kinds) = (3, vec![AppleKind::Golden, AppleKind::Timed]);
+    assert_eq!(Level::parse(&level.to_text()).unwrap(), level);
+    assert!(Level::parse("size 4 4 4\nextras 1 rotten").is_err());
+    let mut board = level.board(3);
+    for _ in 0..200 {
+        if board.state != State::Alive { break }
+        let direction = bot::Difficulty::Hard.pick(&board, 0);
+        board.snakes[0].direction = direction;
+        board.update();
+        if board.state != State::Alive { break }
+        assert_eq!(board.extras.len(), 3);
+        for apple in &board.extras {
+            assert!(level.extra_kinds.contains(&apple.kind));
+            assert_eq!(board.point(apple.pos), Some(Point::Empty));
+            assert_ne!(apple.pos, board.apple_pos);
+            assert_eq!(board.extras.iter().filter(|i| i.pos == apple.pos).count(), 1);
+        }
+    }
+}
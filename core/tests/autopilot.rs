@@ -0,0 +1,33 @@
+use snake3d_core::*;
+use std::collections::HashSet;
+#[test]
+fn cycles_go_through_every_cell() {
+    for (w, d, h) in [(2, 2, 2), (3, 3, 3), (4, 3, 5), (3, 5, 2), (5, 5, 5), (6, 4, 3)] {
+        let pilot = Autopilot::new(w, d, h).unwrap();
+        let cycle = pilot.cycle();
+        for i in 0..cycle.len() {
+            assert_eq!(cycle[i].manhattan(cycle[(i + 1) % cycle.len()]), 1, "{w}x{d}x{h} breaks at {i}");
+        }
+        let mut cells: HashSet<IVec3> = cycle.into_iter().collect();
+        // only boards with every side odd leave a corner out
+        assert_eq!(pilot.spare().is_some(), w % 2 == 1 && d % 2 == 1 && h % 2 == 1);
+        cells.extend(pilot.spare());
+        assert_eq!(cells.len(), w * d * h);
+    }
+    assert!(Autopilot::new(4, 1, 4).is_none());
+}
+#[test]
+fn autopilot_fills_the_board() {
+    for (w, d, h) in [(2, 2, 2), (3, 3, 3), (4, 4, 4), (3, 5, 7), (5, 5, 5), (6, 3, 2)] {
+        let pilot = Autopilot::new(w, d, h).unwrap();
+        for seed in 0..4 {
+            let mut board = Level::new(w, d, h).board(seed);
+            while board.state == State::Alive && board.ticks < 100_000 {
+                let direction = pilot.pick(&board, 0);
+                board.snakes[0].turn(direction);
+                board.update();
+            }
+            assert_eq!(board.state, State::Win, "{w}x{d}x{h} seed {seed}");
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use snake3d_core::{*, bot::Difficulty};
+#[test]
+fn closures_can_steer() {
+    // straight on into the far side every game
+    let stats = Batch::new(Level::new(4, 4, 4), 10).run(&mut [Box::new(|_: &Board, _| Direction::Forward)]);
+    assert_eq!((stats.games, stats.losses, stats.deaths), (10, 10, 10));
+    assert_eq!(stats.causes, [10, 0, 0, 0, 0]);
+    assert_eq!(stats.average_ticks_to_death(), Some(4.0));
+    assert_eq!(stats.lengths.values().sum::<usize>(), 10);
+}
+#[test]
+fn autopilot_wins_every_game() {
+    let stats = Batch::new(Level::new(3, 3, 3), 20).run(&mut [Box::new(Autopilot::new(3, 3, 3).unwrap())]);
+    assert_eq!(stats.win_rate(), 1.0);
+    assert_eq!(stats.average_ticks_to_death(), None);
+    assert_eq!(stats.lengths.into_iter().collect::<Vec<_>>(), [(27, 20)]);
+}
+#[test]
+fn every_snake_gets_a_controller() {
+    let mut level = Level::new(5, 5, 5);
+    level.set_opponents(1, Difficulty::Hard);
+    let mut batch = Batch::new(level, 10);
+    batch.seed = 100;
+    let stats = batch.run(&mut [Box::new(Difficulty::Easy), Box::new(Difficulty::Hard)]);
+    assert_eq!(stats.wins + stats.losses + stats.stuck, 10);
+    assert_eq!(stats.winners.iter().sum::<usize>(), stats.wins);
+    // the same seeds play out the same
+    assert_eq!(batch.run(&mut [Box::new(Difficulty::Easy), Box::new(Difficulty::Hard)]), stats);
+}
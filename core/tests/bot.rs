@@ -0,0 +1,51 @@
+use snake3d_core::{*, bot::Difficulty};
+// the length a bot gets to on its own, summed over a few seeds.
+fn grows(difficulty: Difficulty) -> usize {
+    (0..20).map(|seed| {
+        let mut board = Level::new(4, 4, 4).board(seed);
+        while board.state == State::Alive && board.ticks < 5000 {
+            let direction = difficulty.pick(&board, 0);
+            board.snakes[0].turn(direction);
+            board.update();
+        }
+        assert_ne!(board.state, State::Alive, "{difficulty:?} got stuck on seed {seed}");
+        board.snakes[0].body.len()
+    }).sum()
+}
+#[test]
+fn harder_bots_grow_longer() {
+    let [easy, medium, hard] = Difficulty::ALL.map(grows);
+    assert!(easy < medium && medium < hard, "{easy} {medium} {hard}");
+}
+#[test]
+fn medium_goes_round_walls() {
+    let mut level = Level::new(5, 5, 1);
+    level.snakes[0].pos = ivec3(2, 0, 0);
+    // a wall between the snake and the apple, open at the right
+    for x in 0..4 { level.walls.push(ivec3(x, 1, 0)) }
+    level.apples.push(ivec3(2, 3, 0));
+    assert_eq!(Difficulty::Medium.pick(&level.board(0), 0), Direction::Right);
+}
+#[test]
+fn opponents_are_kept_in_level_files() {
+    let mut level = Level::new(4, 4, 4);
+    level.set_opponents(2, Difficulty::Hard);
+    assert_eq!(level.snakes.iter().map(|i| i.bot).collect::<Vec<_>>(), [None, Some(Difficulty::Hard), Some(Difficulty::Hard)]);
+    assert_eq!(Level::parse(&level.to_text()).unwrap(), level);
+    assert!(Level::parse("size 4 4 4\nsnake 0 0 0 forward 1 tricky").is_err());
+}
+#[test]
+fn tails_stay_put_when_their_snake_eats() {
+    let mut level = Level::new(5, 2, 1);
+    level.snakes[0].pos = ivec3(0, 0, 0);
+    level.snakes.push(Spawn { pos: ivec3(3, 0, 0), direction: Direction::Right, length: 2, bot: None });
+    let tail = ivec3(2, 0, 0);
+    level.apples.push(ivec3(1, 0, 0));
+    assert!(bot::is_safe(&level.board(0), 0, tail));
+    level.apples[0] = ivec3(4, 0, 0);
+    let mut board = level.board(0);
+    assert!(!bot::is_safe(&board, 0, tail));
+    // a waiting turn takes it away from the apple
+    board.snakes[1].turn(Direction::Forward);
+    assert!(bot::is_safe(&board, 0, tail));
+}
@@ -0,0 +1,27 @@
+use snake3d_core::*;
+// three unnamed levels and two sharing a name, none of them beaten.
+fn campaign() -> Campaign {
+    let mut levels = vec![Level::new(4, 4, 4); 5];
+    (levels[3].name, levels[4].name) = ("Twins".into(), "Twins".into());
+    let files = (0..5).map(|i| format!("{i}.txt")).collect();
+    Campaign { levels, files, completed: vec![false; 5] }
+}
+#[test]
+fn progress_is_kept_by_file() {
+    let path = std::env::temp_dir().join(format!("snake3d-progress-{}.txt", std::process::id()));
+    let mut beaten = campaign();
+    beaten.complete(1);
+    beaten.complete(3);
+    beaten.save_progress(&path).unwrap();
+    let mut loaded = campaign();
+    loaded.load_progress(&path).unwrap();
+    assert_eq!(loaded.completed, [false, true, false, true, false]);
+    assert_eq!(loaded.furthest(), 4);
+
+    // older progress files have level names, which are ignored when they're ambiguous
+    std::fs::write(&path, "completed \ncompleted Twins\n").unwrap();
+    let mut loaded = campaign();
+    loaded.load_progress(&path).unwrap();
+    assert_eq!(loaded.completed, [false; 5]);
+    std::fs::remove_file(&path).unwrap();
+}
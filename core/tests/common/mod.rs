@@ -0,0 +1,11 @@
+use snake3d_core::*;
+// an 8x8x8 board with a snake for each of `snakes`, as head, direction and length, and
+// the apple out of the way in the far corner.
+pub fn level(snakes: &[(IVec3, Direction, usize)]) -> Level {
+    let mut level = Level::new(8, 8, 8);
+    level.snakes = snakes.iter().map(|(pos, direction, length)| {
+        Spawn { pos: *pos, direction: *direction, length: *length, bot: None }
+    }).collect();
+    level.apples = vec![ivec3(7, 7, 7)];
+    level
+}
@@ -0,0 +1,47 @@
+use snake3d_core::*;
+// the snake at the corner heading forward, with the apple 3 ahead of it.
+fn env() -> Env {
+    let mut level = Level::new(4, 4, 4);
+    level.apples = vec![ivec3(0, 3, 0), ivec3(3, 3, 3)];
+    let mut env = Env::new(level);
+    env.rewards = Rewards { tick: -0.01, closer: 0.1, ..Rewards::default() };
+    env
+}
+#[test]
+fn observations_show_the_board() {
+    let mut env = env();
+    let observation = env.reset(0);
+    assert_eq!(observation.cells.len(), 64);
+    assert_eq!(observation.cells[0], Observation::SNAKE);
+    assert_eq!(observation.cells.iter().filter(|i| **i != Observation::EMPTY).count(), 1);
+    assert_eq!((observation.head, observation.apple), (IVec3::ZERO, ivec3(0, 3, 0)));
+    assert_eq!(observation.to_vec()[64..], [0.0, 0.0, 0.0, 0.0, 3.0, 0.0]);
+}
+#[test]
+fn rewards_add_up() {
+    let mut env = env();
+    env.reset(0);
+    let (_, reward, done, _) = env.step(Direction::Forward);
+    assert_eq!((reward, done), (-0.01 + 0.1, false));
+    env.step(Direction::Forward);
+    let (observation, reward, done, info) = env.step(Direction::Forward);
+    assert_eq!((reward, done), (-0.01 + 1.0, false));
+    assert_eq!((info.length, info.apples), (2, 1));
+    assert_eq!(observation.apple, ivec3(3, 3, 3));
+    let (_, reward, done, info) = env.step(Direction::Forward);
+    assert_eq!((reward, done), (-0.01 - 1.0, true));
+    assert_eq!((info.state, info.death), (State::Dead, Some(Death::Edge)));
+}
+#[test]
+fn finished_environments_start_again() {
+    let mut envs = VecEnv::new(Level::new(4, 4, 4), 3);
+    let first = envs.reset(10);
+    let mut env = Env::new(Level::new(4, 4, 4));
+    assert_eq!(first[2], env.reset(12));
+    let steps = envs.step(&[Direction::Forward, Direction::Left, Direction::Forward]);
+    assert_eq!(steps.iter().map(|i| i.2).collect::<Vec<_>>(), [false, true, false]);
+    // the one that went off the edge is on its next seed
+    assert_eq!(steps[1].3.death, Some(Death::Edge));
+    assert_eq!(steps[1].0, env.reset(11 + 3));
+    assert_eq!(steps[0].0.head, ivec3(0, 1, 0));
+}
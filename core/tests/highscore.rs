@@ -0,0 +1,71 @@
+use snake3d_core::*;
+fn entry(points: u64, seed: u64) -> ScoreEntry {
+    ScoreEntry { points, date: "2024-02-29".into(), seed, replay: format!("replays/{seed}.s3dr") }
+}
+#[test]
+fn scores_are_ranked_best_first() {
+    let mut scores = HighScores::default();
+    assert_eq!(scores.insert("a", entry(50, 1)), Some(0));
+    assert_eq!(scores.insert("a", entry(80, 2)), Some(0));
+    assert_eq!(scores.insert("a", entry(60, 3)), Some(1));
+    // ties go under the older score and nothing isn't a score
+    assert_eq!(scores.insert("a", entry(60, 4)), Some(2));
+    assert_eq!(scores.insert("a", entry(0, 5)), None);
+    let seeds: Vec<u64> = scores.table("a").iter().map(|i| i.seed).collect();
+    assert_eq!(seeds, [2, 3, 4, 1]);
+    assert_eq!(scores.best("a").unwrap().points, 80);
+    assert!(scores.table("b").is_empty() && scores.best("b").is_none());
+}
+#[test]
+fn tables_keep_the_top_ten() {
+    let mut scores = HighScores::default();
+    for i in 1..=TABLE_SIZE as u64 { scores.insert("a", entry(i * 10, i)); }
+    assert_eq!(scores.insert("a", entry(5, 99)), None);
+    assert_eq!(scores.insert("a", entry(10, 99)), None);
+    assert_eq!(scores.insert("a", entry(15, 99)), Some(TABLE_SIZE - 1));
+    let table = scores.table("a");
+    assert_eq!(table.len(), TABLE_SIZE);
+    assert_eq!((table[0].points, table[TABLE_SIZE - 1].points), (100, 15));
+}
+#[test]
+fn scores_survive_text() {
+    let mut scores = HighScores::default();
+    scores.insert("4x4x4 speed 3 wrap none open free perfect", entry(120, 7));
+    scores.insert("4x4x4 speed 3 wrap none open free perfect", entry(40, 8));
+    scores.insert("6x6x4 speed 2 wrap xy walls Pillars", entry(300, 9));
+    let text = scores.to_text();
+    assert_eq!(text.lines().next(), Some("4x4x4 speed 3 wrap none open free perfect\t120\t2024-02-29\t7\treplays/7.s3dr"));
+    assert_eq!(HighScores::parse(&text), scores);
+    // broken lines are skipped, keeping the rest
+    let broken = format!("{text}nonsense\nkey\tlots\t2024-01-01\t1\t\n");
+    assert_eq!(HighScores::parse(&broken), scores);
+
+    let path = std::env::temp_dir().join(format!("snake3d-scores-{}.txt", std::process::id()));
+    scores.save(&path).unwrap();
+    assert_eq!(HighScores::load(&path).unwrap(), scores);
+    std::fs::remove_file(&path).unwrap();
+}
+#[test]
+fn keys_tell_games_apart() {
+    let mut level = Level::new(4, 4, 4);
+    assert_eq!(score_key(&level, 3), "4x4x4 speed 3 wrap none open free perfect");
+    (level.wrap, level.walls, level.name) = ([true, false, true], vec![ivec3(1, 1, 1)], "Pillars".into());
+    assert_eq!(score_key(&level, 0), "4x4x4 speed 0 wrap xz walls Pillars");
+    assert_eq!(date(0), "1970-01-01");
+    assert_eq!(date(951782400), "2000-02-29");
+}
+#[test]
+fn extra_apples_get_their_own_tables() {
+    let mut level = Level::new(4, 4, 4);
+    let plain = score_key(&level, 3);
+    (level.extra_count, level.extra_kinds) = (2, vec![AppleKind::Golden, AppleKind::Timed]);
+    let extras = score_key(&level, 3);
+    assert_eq!(extras, format!("{plain} extras 2 golden,timed"));
+    level.extra_kinds.clear();
+    assert_eq!(score_key(&level, 3), format!("{plain} extras 2 normal"));
+
+    let mut scores = HighScores::default();
+    scores.insert(&extras, entry(900, 1));
+    scores.insert(&plain, entry(100, 2));
+    assert_eq!((scores.best(&plain).unwrap().seed, scores.best(&extras).unwrap().seed), (2, 1));
+}
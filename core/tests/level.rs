@@ -0,0 +1,52 @@
+use snake3d_core::*;
+// the error a level fails to parse with, as "line: message".
+fn parse_error(text: &str) -> String {
+    let error = Level::parse(text).unwrap_err();
+    format!("{}: {}", error.line, error.message)
+}
+#[test]
+fn levels_have_at_most_max_players_snakes() {
+    let snakes: String = (0..=MAX_PLAYERS).map(|i| format!("snake {i} 0 0 forward\n")).collect();
+    assert_eq!(parse_error(&format!("size 8 8 8\n{snakes}")), "6: there can be at most 4 snakes");
+    let mut level = Level::new(8, 8, 8);
+    level.set_players(MAX_PLAYERS);
+    assert_eq!(Level::parse(&level.to_text()).unwrap().snakes.len(), MAX_PLAYERS);
+    level.snakes.push(Spawn { pos: ivec3(4, 4, 4), direction: Direction::Up, length: 1, bot: None });
+    assert!(Level::parse(&level.to_text()).is_err());
+}
+#[test]
+fn broken_levels_say_what_and_where() {
+    let cases = [
+        ("size 4 4", "1: size is missing a value"),
+        ("size 4 four 4", "1: four is not a number"),
+        ("size 4 4 300", "1: each side of the board must be from 2 to 255"),
+        ("size 4 4 4\nwrap xw", "2: w is not an axis, use x, y or z"),
+        ("// walls\nlayer 0\n....", "2: size has to come before any layers"),
+        ("size 2 2 2\nlayer 2\n..\n..", "2: layer 2 is above the board"),
+        ("size 2 2 2\nlayer 0\n...\n..", "3: rows need to be 2 cells wide"),
+        ("size 2 2 2\nlayer 0\n.o\n..", "3: o is not a cell, use # or ."),
+        ("size 2 2 2\nlayer 1\n#.\n..\nlayer 1\n.#\n..", "5: layer 1 is already set"),
+        ("size 2 2 2\nlayer 0\n..", "2: layer 0 needs 2 rows"),
+        ("size 4 4 4\nsnake 0 0 0 sideways", "2: sideways is not a direction"),
+        ("size 4 4 4\nsnake 0 0 0 forward 0", "2: the snake needs a length of at least 1"),
+        ("size 4 4 4\nspeed 7", "2: speed must be from 0 to 6"),
+        ("size 4 4 4\nshape cube", "2: unknown setting shape"),
+        ("name Empty", "0: level has no size"),
+        ("size 4 4 4\nsnake 0 1 0 forward 3", "0: the snake doesn't fit on the board"),
+        ("size 2 2 2\nlayer 0\n..\n#.\nsnake 0 1 0 forward 2", "0: the snake starts inside a wall"),
+        ("size 4 4 4\napple 0 4 0", "0: apple at 0 4 0 is off the board or in a wall")
+    ];
+    for (text, message) in cases {
+        assert_eq!(parse_error(text), message, "parsing {text:?}");
+    }
+}
+#[test]
+fn walls_are_checked() {
+    let mut level = Level::new(4, 4, 4);
+    level.walls = vec![ivec3(1, 1, 1), ivec3(2, 1, 1)];
+    assert!(level.validate().is_ok());
+    level.walls.push(ivec3(1, 1, 1));
+    assert_eq!(level.validate().unwrap_err().message, "wall at 1 1 1 is there twice");
+    level.walls = vec![ivec3(1, 4, 1)];
+    assert_eq!(level.validate().unwrap_err().message, "wall at 1 4 1 is off the board");
+}
@@ -0,0 +1,70 @@
+use snake3d_core::*;
+fn game() -> Replay {
+    let mut level = Level::new(6, 6, 4);
+    level.set_players(2);
+    // plays on after one snake dies so peers have to keep in step for longer
+    level.versus = Versus::HighScore;
+    Replay::new(level, 7, 3)
+}
+#[test]
+fn packets_round_trip() {
+    let packets = [
+        Packet::Join,
+        Packet::Start { player: 1, delay: 3, peers: vec![None, Some("127.0.0.1:4000".parse().unwrap())], game: game() },
+        Packet::Input { player: 1, first_tick: 40, directions: vec![Direction::Up, Direction::Left], hash: Some((41, 123)) },
+        Packet::Input { player: 0, first_tick: 0, directions: vec![], hash: None }
+    ];
+    for i in packets {
+        assert_eq!(Packet::from_bytes(&i.to_bytes()).unwrap(), i);
+    }
+    assert!(Packet::from_bytes(b"S3DN\x01\x09").is_err());
+}
+#[test]
+fn ticks_wait_for_every_player() {
+    let board = game().board();
+    let mut peer = Lockstep::new(&board, 0, 2);
+    let mut board = game().board();
+    // the first `delay` ticks are known
+    assert!(peer.step(&mut board) && peer.step(&mut board));
+    peer.push_local(Direction::Right);
+    assert!(!peer.step(&mut board));
+    assert_eq!(peer.tick, 2);
+}
+// two peers swapping packets, losing some, each with its own board and bot.
+#[test]
+fn peers_stay_in_step() {
+    let mut boards = [game().board(), game().board()];
+    let mut peers = [Lockstep::new(&boards[0], 0, 3), Lockstep::new(&boards[1], 1, 3)];
+    let mut sent = 0;
+    while boards.iter().any(|i| i.state == State::Alive) && peers[0].tick < 500 {
+        for i in 0..2 {
+            if peers[i].needs_local() { peers[i].push_local(bot::greedy(&boards[i], i)) }
+            // every third packet goes missing
+            sent += 1;
+            if sent % 3 != 0 {
+                let packet = peers[i].packet();
+                peers[1 - i].receive(&Packet::from_bytes(&packet.to_bytes()).unwrap());
+            }
+            peers[i].step(&mut boards[i]);
+        }
+    }
+    assert!(peers[0].tick > 10);
+    assert_eq!(peers[0].tick, peers[1].tick);
+    assert_eq!(boards[0].hash(), boards[1].hash());
+    assert!(peers.iter().all(|i| i.desync.is_none()));
+}
+#[test]
+fn drifting_boards_are_caught() {
+    let mut boards = [game().board(), game().board()];
+    let mut peers = [Lockstep::new(&boards[0], 0, 1), Lockstep::new(&boards[1], 1, 1)];
+    boards[1].snakes[1].score.points = 5;
+    for _ in 0..3 {
+        for i in 0..2 {
+            peers[i].push_local(boards[i].snakes[i].direction);
+            let packet = peers[i].packet();
+            peers[1 - i].receive(&packet);
+            peers[i].step(&mut boards[i]);
+        }
+    }
+    assert_eq!(peers[0].desync, Some(0));
+}
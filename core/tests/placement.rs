@@ -0,0 +1,66 @@
+use snake3d_core::*;
+// a 6x6x6 board with a snake 5 long and a row of walls, so some runs of taken cells
+// are long. the apple is moved once on each seed, counting where it lands.
+fn counts(scanned: bool) -> Vec<usize> {
+    let mut level = Level::new(6, 6, 6);
+    level.snakes[0] = Spawn { pos: ivec3(0, 4, 0), direction: Direction::Forward, length: 5, bot: None };
+    level.walls = (0..6).map(|x| ivec3(x, 2, 3)).collect();
+    let mut counts = vec![0; 6 * 6 * 6];
+    for seed in 0..20000 {
+        let mut board = level.board(seed);
+        board.scanned_apples = scanned;
+        let pos = board.spawn_apple().unwrap();
+        assert_eq!(board.point(pos), Some(Point::Empty));
+        counts[(pos.x + pos.y * 6 + pos.z * 36) as usize] += 1;
+    }
+    counts
+}
+// pearson's chi squared for the cells apples landed on being as likely as each other,
+// over how far it can be off by chance: about four standard deviations over its average.
+fn uneven(counts: &[usize]) -> f64 {
+    let counts: Vec<f64> = counts.iter().filter(|i| **i > 0).map(|i| *i as f64).collect();
+    let expected = counts.iter().sum::<f64>() / counts.len() as f64;
+    let chi: f64 = counts.iter().map(|i| (i - expected).powi(2) / expected).sum();
+    let free = counts.len() as f64 - 1.0;
+    chi / (free + 4.0 * (2.0 * free).sqrt())
+}
+#[test]
+fn apples_land_evenly() {
+    let counts = counts(false);
+    assert_eq!(counts.iter().filter(|i| **i > 0).count(), 6 * 6 * 6 - 5 - 6);
+    assert!(uneven(&counts) < 1.0);
+}
+#[test]
+fn scanned_apples_favour_cells_after_runs() {
+    assert!(uneven(&counts(true)) > 1.0);
+}
+#[test]
+fn a_full_board_has_nowhere_for_the_apple() {
+    let mut board = Board::new(2, 2, 2, 0);
+    for pos in [ivec3(1, 0, 0), ivec3(1, 1, 0), ivec3(0, 0, 1), ivec3(1, 0, 1), ivec3(0, 1, 1), ivec3(1, 1, 1)] {
+        assert!(board.add_wall(pos));
+    }
+    assert_eq!(board.spawn_apple(), Some(ivec3(0, 1, 0)));
+    assert!(board.add_wall(ivec3(0, 1, 0)));
+    assert_eq!(board.spawn_apple(), None);
+    assert_eq!(board.apple_pos, ivec3(0, 1, 0));
+}
+#[test]
+fn apples_only_land_on_empty_cells() {
+    let mut level = Level::new(5, 5, 5);
+    level.set_players(2);
+    level.walls = vec![ivec3(2, 2, 2), ivec3(2, 3, 2)];
+    (level.extra_count, level.extra_kinds) = (4, AppleKind::ALL.to_vec());
+    for seed in 0..20 {
+        let mut board = level.board(seed);
+        while board.state == State::Alive && board.ticks < 2000 {
+            for i in 0..2 {
+                if board.snakes[i].alive { board.snakes[i].direction = bot::Difficulty::Medium.pick(&board, i) }
+            }
+            board.update();
+            if board.state != State::Alive { break }
+            assert_eq!(board.point(board.apple_pos), Some(Point::Empty));
+            assert!(board.extras.iter().all(|i| board.point(i.pos) == Some(Point::Empty) && i.pos != board.apple_pos));
+        }
+    }
+}
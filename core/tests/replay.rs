@@ -0,0 +1,56 @@
+use snake3d_core::*;
+// the hard bot playing a walled level, recorded until it ends or for 500 ticks.
+fn recorded() -> (Replay, Board) {
+    let mut level = Level::new(6, 6, 4);
+    level.walls = vec![ivec3(2, 2, 0), ivec3(3, 3, 1)];
+    let mut replay = Replay::new(level, 9, 2);
+    let mut board = replay.board();
+    while board.state == State::Alive && board.ticks < 500 {
+        board.snakes[0].direction = bot::Difficulty::Hard.pick(&board, 0);
+        board.update();
+        replay.record(&board);
+    }
+    (replay, board)
+}
+#[test]
+fn replays_survive_bytes() {
+    let (replay, board) = recorded();
+    let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+    assert_eq!(loaded, replay);
+    assert!(Replay::from_bytes(&replay.to_bytes()[..20]).is_err());
+    let (mut player, mut played) = ReplayPlayer::new(loaded);
+    while !player.finished(&played) { player.step(&mut played) }
+    assert_eq!(played.hash(), board.hash());
+}
+#[test]
+fn version_4_replays_still_load() {
+    let (replay, _) = recorded();
+    // version 4 had no flags byte, after the magic, version, seed and speed
+    let mut bytes = replay.to_bytes();
+    bytes[4] = 4;
+    bytes.remove(14);
+    let loaded = Replay::from_bytes(&bytes).unwrap();
+    assert!(loaded.allow_reverse && loaded.scanned_apples);
+    assert_eq!((loaded.level, loaded.seed, loaded.speed, loaded.inputs), (replay.level, replay.seed, replay.speed, replay.inputs));
+    bytes[4] = 8;
+    assert!(Replay::from_bytes(&bytes).is_err());
+}
+#[test]
+fn seeking_matches_playing_through() {
+    let (replay, _) = recorded();
+    let ticks = replay.inputs.len();
+    assert!(ticks > 20);
+    let (mut seeker, mut sought) = ReplayPlayer::new(replay.clone());
+    // forwards, back, to the end and back to near the start
+    for tick in [ticks / 2, ticks / 4, ticks, 3] {
+        seeker.seek(&mut sought, tick);
+        let (mut player, mut board) = ReplayPlayer::new(replay.clone());
+        for _ in 0..tick { player.step(&mut board) }
+        assert_eq!(seeker.tick(), tick);
+        assert_eq!(sought.hash(), board.hash());
+    }
+    seeker.rewind(&mut sought, 2);
+    let (mut player, mut board) = ReplayPlayer::new(replay);
+    player.step(&mut board);
+    assert_eq!((seeker.tick(), sought.hash()), (1, board.hash()));
+}
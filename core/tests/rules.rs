@@ -0,0 +1,281 @@
+use snake3d_core::*;
+mod common;
+// a snake 3 long heading forward along x = 0, y = 2..=4, with the apple out of the way.
+fn board() -> Board {
+    common::level(&[(ivec3(0, 4, 0), Direction::Forward, 3)]).board(0)
+}
+
+#[test]
+fn reversing_is_ignored() {
+    let mut board = board();
+    board.snakes[0].direction = Direction::Back;
+    board.update();
+    assert_eq!(board.state, State::Alive);
+    assert_eq!(board.snakes[0].pos, ivec3(0, 5, 0));
+    assert_eq!(board.snakes[0].direction, Direction::Forward);
+}
+#[test]
+fn reversing_can_be_allowed() {
+    let mut board = board();
+    board.allow_reverse = true;
+    board.snakes[0].direction = Direction::Back;
+    board.update();
+    assert_eq!(board.state, State::Dead);
+}
+#[test]
+fn short_snakes_can_turn_round() {
+    let mut board = common::level(&[(ivec3(0, 4, 0), Direction::Forward, 1)]).board(0);
+    board.snakes[0].direction = Direction::Back;
+    board.update();
+    assert_eq!(board.state, State::Alive);
+    assert_eq!(board.snakes[0].pos, ivec3(0, 3, 0));
+}
+#[test]
+fn reversing_across_a_wrapped_face_is_ignored() {
+    let mut level = Level::new(4, 4, 4);
+    level.wrap = [false, true, false];
+    level.snakes[0] = Spawn { pos: ivec3(0, 0, 0), direction: Direction::Back, length: 2, bot: None };
+    level.apples = vec![ivec3(3, 3, 3)];
+    let mut board = level.board(0);
+    board.update();
+    assert_eq!(board.snakes[0].pos, ivec3(0, 3, 0));
+    assert!(board.is_reverse(0, Direction::Forward));
+    board.snakes[0].direction = Direction::Forward;
+    board.update();
+    assert_eq!(board.snakes[0].pos, ivec3(0, 2, 0));
+}
+#[test]
+fn queued_reverse_is_skipped_for_the_next_turn() {
+    let mut board = board();
+    board.snakes[0].turn(Direction::Back);
+    board.snakes[0].turn(Direction::Right);
+    board.update();
+    assert_eq!(board.snakes[0].pos, ivec3(0, 5, 0));
+    board.update();
+    assert_eq!(board.snakes[0].pos, ivec3(1, 5, 0));
+    assert_eq!(board.state, State::Alive);
+}
+#[test]
+fn turns_past_the_queue_depth_are_dropped() {
+    let mut board = board();
+    let snake = &mut board.snakes[0];
+    snake.queue_depth = 2;
+    for i in [Direction::Right, Direction::Up, Direction::Left] { snake.turn(i) }
+    assert_eq!(snake.queue, [Direction::Right, Direction::Up]);
+    // there's always room for one
+    (snake.queue_depth, snake.queue) = (0, Default::default());
+    for i in [Direction::Right, Direction::Up] { snake.turn(i) }
+    assert_eq!(snake.queue, [Direction::Right]);
+}
+#[test]
+fn turns_that_change_nothing_are_dropped() {
+    let mut board = board();
+    let snake = &mut board.snakes[0];
+    snake.turn(Direction::Forward);
+    assert!(snake.queue.is_empty());
+    for i in [Direction::Right, Direction::Right, Direction::Up, Direction::Right] { snake.turn(i) }
+    assert_eq!(snake.queue, [Direction::Right, Direction::Up, Direction::Right]);
+}
+#[test]
+fn one_queued_turn_is_taken_each_tick() {
+    let mut board = board();
+    board.snakes[0].turn(Direction::Right);
+    board.snakes[0].turn(Direction::Up);
+    board.update();
+    assert_eq!((board.snakes[0].pos, board.snakes[0].queue.len()), (ivec3(1, 4, 0), 1));
+    board.update();
+    assert_eq!((board.snakes[0].pos, board.snakes[0].queue.len()), (ivec3(1, 4, 1), 0));
+    board.update();
+    assert_eq!((board.snakes[0].pos, board.snakes[0].direction), (ivec3(1, 4, 2), Direction::Up));
+}
+#[test]
+fn replays_keep_the_reverse_rule() {
+    let mut replay = Replay::new(Level::new(4, 4, 4), 1, 3);
+    replay.allow_reverse = true;
+    let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+    assert!(loaded.allow_reverse);
+    assert!(loaded.board().allow_reverse);
+}
+
+// two snakes 3 long heading forward along x = 0 and x = 2, with heads at y = 3.
+fn versus(versus: Versus) -> Board {
+    let mut level = common::level(&[(ivec3(0, 3, 0), Direction::Forward, 3), (ivec3(2, 3, 0), Direction::Forward, 3)]);
+    level.versus = versus;
+    level.board(0)
+}
+#[test]
+fn heads_meeting_both_die() {
+    let mut board = versus(Versus::LastStanding);
+    board.snakes[0].direction = Direction::Right;
+    board.snakes[1].direction = Direction::Left;
+    board.update();
+    assert!(!board.snakes[0].alive && !board.snakes[1].alive);
+    assert_eq!(board.snakes[0].death, Some(Death::HeadOn));
+    assert_eq!((board.state, board.winner), (State::Dead, None));
+}
+#[test]
+fn heads_swapping_places_both_die() {
+    let mut board = common::level(&[(ivec3(1, 0, 0), Direction::Right, 1), (ivec3(2, 0, 0), Direction::Left, 1)]).board(0);
+    board.update();
+    assert!(!board.snakes[0].alive && !board.snakes[1].alive);
+    assert_eq!((board.snakes[0].death, board.snakes[1].death), (Some(Death::HeadOn), Some(Death::HeadOn)));
+    assert_eq!(board.point(ivec3(1, 0, 0)), Some(Point::Empty));
+    assert_eq!(board.point(ivec3(2, 0, 0)), Some(Point::Empty));
+}
+#[test]
+fn deaths_say_what_the_snake_hit() {
+    let mut board = board();
+    board.snakes[0].direction = Direction::Left;
+    board.update();
+    assert_eq!(board.snakes[0].death, Some(Death::Edge));
+
+    let mut board = versus(Versus::HighScore);
+    board.snakes[0].direction = Direction::Right;
+    board.update();
+    board.update();
+    assert_eq!(board.snakes[0].death, Some(Death::Snake));
+    assert_eq!(board.snakes[1].death, None);
+}
+#[test]
+fn last_snake_standing_wins() {
+    let mut board = versus(Versus::LastStanding);
+    board.snakes[1].direction = Direction::Left;
+    board.update();
+    board.snakes[1].direction = Direction::Left;
+    board.update();
+    // the second snake ran into the first one's neck
+    assert!(board.snakes[0].alive && !board.snakes[1].alive);
+    assert_eq!((board.state, board.winner), (State::Win, Some(0)));
+    // and doesn't block anything any more
+    assert_eq!(board.point(board.snakes[1].body[0]), Some(Point::Empty));
+}
+#[test]
+fn high_score_plays_on_until_everyone_is_dead() {
+    let mut board = versus(Versus::HighScore);
+    (board.snakes[0].score.points, board.snakes[1].score.points) = (20, 10);
+    board.snakes[0].direction = Direction::Left;
+    board.update();
+    assert!(!board.snakes[0].alive);
+    assert_eq!(board.state, State::Alive);
+    board.snakes[1].direction = Direction::Right;
+    while board.state == State::Alive { board.update() }
+    assert_eq!(board.snakes[1].pos, ivec3(7, 4, 0));
+    assert_eq!((board.state, board.winner), (State::Win, Some(0)));
+}
+#[test]
+fn snakes_can_follow_a_tail() {
+    let mut board = common::level(&[(ivec3(0, 2, 0), Direction::Forward, 3), (ivec3(0, 5, 0), Direction::Forward, 3)]).board(0);
+    board.update();
+    assert!(board.snakes.iter().all(|i| i.alive));
+    assert_eq!(board.snakes[0].pos, ivec3(0, 3, 0));
+}
+#[test]
+fn replays_keep_every_snake() {
+    let mut replay = Replay::new(Level::new(8, 8, 8), 0, 3);
+    replay.level.set_players(2);
+    let mut board = replay.board();
+    board.update();
+    replay.record(&board);
+    let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+    assert_eq!(loaded.inputs, vec![vec![Direction::Forward, Direction::Back]]);
+    assert_eq!(loaded.board().snakes.len(), 2);
+}
+#[test]
+fn old_replays_keep_scanning_for_apples() {
+    let mut replay = Replay::new(Level::new(4, 4, 4), 1, 3);
+    assert!(!Replay::from_bytes(&replay.to_bytes()).unwrap().scanned_apples);
+    replay.scanned_apples = true;
+    assert!(Replay::from_bytes(&replay.to_bytes()).unwrap().board().scanned_apples);
+    // version 6, from before apples were placed evenly
+    replay.scanned_apples = false;
+    let mut bytes = replay.to_bytes();
+    bytes[4] = 6;
+    assert!(Replay::from_bytes(&bytes).unwrap().scanned_apples);
+}
+
+#[test]
+fn apples_are_worth_more_fast_and_on_big_boards() {
+    let score = Score::default();
+    assert_eq!(score.apple_points(0, ivec3(4, 4, 4)), 100);
+    assert_eq!(score.apple_points(3, ivec3(8, 8, 8)), 80);
+    assert_eq!(score.apple_points(6, ivec3(8, 8, 8)), 40);
+    assert_eq!(score.apple_points(2, ivec3(2, 2, 3)), 30);
+}
+#[test]
+fn combos_build_up_then_break() {
+    let mut score = Score::default();
+    let size = ivec3(4, 4, 4);
+    assert_eq!(Score::combo_ticks(size), 6);
+    // each within 6 ticks of the last, up to the most a combo goes to
+    let points: Vec<u64> = [10, 16, 22, 28, 34, 40].iter().map(|i| score.eat(*i, 2, size)).collect();
+    assert_eq!(points, [60, 120, 180, 240, 300, 300]);
+    assert_eq!(score.combo, MAX_COMBO);
+    // one tick too late
+    assert_eq!(score.eat(47, 2, size), 60);
+    assert_eq!((score.combo, score.best_combo, score.points), (0, MAX_COMBO, 1260));
+}
+#[test]
+fn eating_scores_on_the_board() {
+    let mut board = goal_board(Goal::Perfect);
+    board.update();
+    assert_eq!(board.snakes[0].score.points, 80);
+    board.update();
+    assert_eq!(board.snakes[0].score.points, 80 + 160);
+    assert_eq!(board.snakes[0].score.last_apple(), Some(2));
+}
+
+// a snake 1 long heading forward from 0 4 0 with apples in a row in front of it.
+fn goal_board(goal: Goal) -> Board {
+    let mut level = common::level(&[(ivec3(0, 4, 0), Direction::Forward, 1)]);
+    level.apples.splice(0..0, [ivec3(0, 5, 0), ivec3(0, 6, 0)]);
+    level.goal = goal;
+    level.board(0)
+}
+// plays `ticks` ticks, checking the game is only won on the last one.
+fn won_after(mut board: Board, ticks: usize) {
+    for _ in 1..ticks {
+        board.update();
+        assert_eq!(board.state, State::Alive);
+        assert!(board.goal_left(0) > 0);
+    }
+    board.update();
+    assert_eq!((board.state, board.winner, board.goal_left(0)), (State::Win, Some(0), 0));
+}
+#[test]
+fn length_goals_are_met() {
+    won_after(goal_board(Goal::Length(3)), 2);
+}
+#[test]
+fn apple_goals_are_met() {
+    won_after(goal_board(Goal::Apples(2)), 2);
+}
+#[test]
+fn survive_goals_are_met() {
+    won_after(goal_board(Goal::Survive(3)), 3);
+}
+#[test]
+fn reach_goals_are_met() {
+    won_after(goal_board(Goal::Reach(ivec3(0, 7, 0))), 3);
+}
+#[test]
+fn score_goals_are_met() {
+    // 80 for the first apple then 160 with the combo
+    won_after(goal_board(Goal::Score(200)), 2);
+}
+#[test]
+fn perfect_goals_are_met_by_filling_the_board() {
+    // the bottom layer of a 2x2x2 board, round from 0 0 0
+    let mut level = Level::new(2, 2, 2);
+    level.walls = vec![ivec3(0, 0, 1), ivec3(1, 0, 1), ivec3(0, 1, 1), ivec3(1, 1, 1)];
+    level.apples = vec![ivec3(0, 1, 0), ivec3(1, 1, 0), ivec3(1, 0, 0)];
+    let mut board = level.board(0);
+    for direction in [Direction::Forward, Direction::Right] {
+        board.snakes[0].direction = direction;
+        board.update();
+        assert_eq!(board.state, State::Alive);
+    }
+    assert_eq!(board.goal_left(0), 1);
+    board.snakes[0].direction = Direction::Back;
+    board.update();
+    assert_eq!((board.state, board.winner, board.snakes[0].body.len()), (State::Win, Some(0), 4));
+}
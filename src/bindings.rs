@@ -0,0 +1,352 @@
+use std::{fs, io, path::Path};
+use thin_engine::{prelude::*, winit::keyboard::PhysicalKey};
+use gilrs::{Axis, Button};
+use crate::gamepad::PadInput;
+#[derive(ToUsize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Up, Down, Left, Right, Forward, Back,
+    ExpandMap, ShrinkMap, SelectAxis, ToggleWrap, SpeedUp, SlowDown,
+    ToggleCampaign, CycleGoal, CycleControls, CyclePlayers, Rebind, ToggleFullscreen, Exit, Mute,
+    WatchReplay, Pause, Step, FastForward, Rewind, ToggleAutopilot,
+    Up2, Down2, Left2, Right2, Forward2, Back2,
+    Up3, Down3, Left3, Right3, Forward3, Back3,
+    Up4, Down4, Left4, Right4, Forward4, Back4
+}
+use Action::*;
+pub const ACTIONS: usize = 44;
+// each player's movement, in the order up, down, left, right, forward, back.
+pub const MOVES: [[Action; 6]; snake3d_core::MAX_PLAYERS] = [
+    [Up,  Down,  Left,  Right,  Forward,  Back],
+    [Up2, Down2, Left2, Right2, Forward2, Back2],
+    [Up3, Down3, Left3, Right3, Forward3, Back3],
+    [Up4, Down4, Left4, Right4, Forward4, Back4]
+];
+impl Action {
+    pub const ALL: [Action; ACTIONS] = [
+        Up, Down, Left, Right, Forward, Back,
+        ExpandMap, ShrinkMap, SelectAxis, ToggleWrap, SpeedUp, SlowDown,
+        ToggleCampaign, CycleGoal, CycleControls, CyclePlayers, Rebind, ToggleFullscreen, Exit, Mute,
+        WatchReplay, Pause, Step, FastForward, Rewind, ToggleAutopilot,
+        Up2, Down2, Left2, Right2, Forward2, Back2,
+        Up3, Down3, Left3, Right3, Forward3, Back3,
+        Up4, Down4, Left4, Right4, Forward4, Back4
+    ];
+    // how it's written in the controls file, and shown with spaces on the rebinding screen.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Up => "up", Down => "down", Left => "left", Right => "right",
+            Forward => "forward", Back => "back",
+            ExpandMap => "expand_map", ShrinkMap => "shrink_map",
+            SelectAxis => "select_axis", ToggleWrap => "toggle_wrap",
+            SpeedUp => "speed_up", SlowDown => "slow_down",
+            ToggleCampaign => "toggle_campaign", CycleGoal => "cycle_goal",
+            CycleControls => "cycle_controls", CyclePlayers => "cycle_players", Rebind => "rebind",
+            ToggleFullscreen => "toggle_fullscreen", Exit => "exit", Mute => "mute",
+            WatchReplay => "watch_replay", Pause => "pause", Step => "step",
+            FastForward => "fast_forward", Rewind => "rewind", ToggleAutopilot => "toggle_autopilot",
+            Up2 => "p2_up", Down2 => "p2_down", Left2 => "p2_left", Right2 => "p2_right",
+            Forward2 => "p2_forward", Back2 => "p2_back",
+            Up3 => "p3_up", Down3 => "p3_down", Left3 => "p3_left", Right3 => "p3_right",
+            Forward3 => "p3_forward", Back3 => "p3_back",
+            Up4 => "p4_up", Down4 => "p4_down", Left4 => "p4_left", Right4 => "p4_right",
+            Forward4 => "p4_forward", Back4 => "p4_back"
+        }
+    }
+}
+// a key or mouse button, or something on a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bind {
+    Input(Input),
+    Pad(PadInput)
+}
+// the inputs for each action, in the order of `Action::ALL`.
+pub type Binds = [Vec<Bind>; ACTIONS];
+pub fn input_map(binds: &Binds) -> InputMap<ACTIONS> {
+    let mut binds = key_binds(binds).into_iter();
+    InputMap::new(Action::ALL.map(|i| (i, binds.next().unwrap())))
+}
+// just the keys and mouse buttons, which winit_input_map handles.
+pub fn key_binds(binds: &Binds) -> [Vec<Input>; ACTIONS] {
+    binds.clone().map(|i| i.into_iter().filter_map(|i| match i { Bind::Input(i) => Some(i), _ => None }).collect())
+}
+// just the gamepad inputs, which `Gamepad` handles.
+pub fn pad_binds(binds: &Binds) -> [Vec<PadInput>; ACTIONS] {
+    binds.clone().map(|i| i.into_iter().filter_map(|i| match i { Bind::Pad(i) => Some(i), _ => None }).collect())
+}
+pub fn default_binds() -> Binds {
+    let keys = |keys: &[KeyCode], pads: &[PadInput]| keys.iter()
+        .map(|i| Bind::Input(Input::keycode(*i)))
+        .chain(pads.iter().map(|i| Bind::Pad(*i)))
+        .collect();
+    let (pad, stick) = (PadInput::Button, PadInput::Stick);
+    Action::ALL.map(|action| match action {
+        Exit             => keys(&[KeyCode::Escape], &[]),
+        ToggleFullscreen => keys(&[KeyCode::KeyF], &[]),
+        Mute             => keys(&[KeyCode::KeyM], &[pad(Button::West)]),
+        SpeedUp          => keys(&[KeyCode::Digit1], &[pad(Button::North)]),
+        // the speed wraps round, so speeding up alone reaches every speed and slowing
+        // down can go on select, leaving a face button for mute
+        SlowDown         => keys(&[KeyCode::Digit2], &[pad(Button::Select)]),
+        ExpandMap        => keys(&[KeyCode::Equal], &[pad(Button::East)]),
+        ShrinkMap        => keys(&[KeyCode::Minus], &[pad(Button::South)]),
+        SelectAxis       => keys(&[KeyCode::Tab], &[pad(Button::LeftThumb)]),
+        ToggleWrap       => keys(&[KeyCode::KeyO], &[pad(Button::RightThumb)]),
+        ToggleCampaign   => keys(&[KeyCode::KeyC], &[]),
+        CycleGoal        => keys(&[KeyCode::KeyG], &[]),
+        CycleControls    => keys(&[KeyCode::KeyV], &[pad(Button::Mode)]),
+        CyclePlayers     => keys(&[KeyCode::KeyN], &[]),
+        Rebind           => keys(&[KeyCode::KeyB], &[]),
+        WatchReplay      => keys(&[KeyCode::KeyR], &[]),
+        Pause            => keys(&[KeyCode::KeyP], &[pad(Button::Start)]),
+        Step             => keys(&[KeyCode::Period], &[]),
+        FastForward      => keys(&[KeyCode::BracketRight], &[pad(Button::RightTrigger2)]),
+        Rewind           => keys(&[KeyCode::BracketLeft], &[pad(Button::LeftTrigger2)]),
+        ToggleAutopilot  => keys(&[KeyCode::KeyT], &[]),
+        Forward          => keys(&[KeyCode::KeyW, KeyCode::ArrowUp], &[pad(Button::DPadUp), stick(Axis::LeftStickY, true)]),
+        Back             => keys(&[KeyCode::KeyS, KeyCode::ArrowDown], &[pad(Button::DPadDown), stick(Axis::LeftStickY, false)]),
+        Down             => keys(&[KeyCode::KeyQ, KeyCode::Enter], &[pad(Button::LeftTrigger)]),
+        Up               => keys(&[KeyCode::KeyE, KeyCode::Space], &[pad(Button::RightTrigger)]),
+        Left             => keys(&[KeyCode::KeyA, KeyCode::ArrowLeft], &[pad(Button::DPadLeft), stick(Axis::LeftStickX, false)]),
+        Right            => keys(&[KeyCode::KeyD, KeyCode::ArrowRight], &[pad(Button::DPadRight), stick(Axis::LeftStickX, true)]),
+        Forward2 => keys(&[KeyCode::KeyI], &[]),      Back2  => keys(&[KeyCode::KeyK], &[]),
+        Left2    => keys(&[KeyCode::KeyJ], &[]),      Right2 => keys(&[KeyCode::KeyL], &[]),
+        Up2      => keys(&[KeyCode::Semicolon], &[]), Down2  => keys(&[KeyCode::KeyU], &[]),
+        Forward3 => keys(&[KeyCode::Numpad8], &[]),   Back3  => keys(&[KeyCode::Numpad5], &[]),
+        Left3    => keys(&[KeyCode::Numpad4], &[]),   Right3 => keys(&[KeyCode::Numpad6], &[]),
+        Up3      => keys(&[KeyCode::Numpad9], &[]),   Down3  => keys(&[KeyCode::Numpad7], &[]),
+        Forward4 => keys(&[KeyCode::Home], &[]),      Back4  => keys(&[KeyCode::End], &[]),
+        Left4    => keys(&[KeyCode::Delete], &[]),    Right4 => keys(&[KeyCode::PageDown], &[]),
+        Up4      => keys(&[KeyCode::PageUp], &[]),    Down4  => keys(&[KeyCode::Insert], &[])
+    })
+}
+// keys that can go in the controls file, written as winit names them.
+const KEYS: [KeyCode; 99] = {
+    use KeyCode::*;
+    [
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM,
+        KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+        Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+        Backquote, Backslash, BracketLeft, BracketRight, Comma, Equal, IntlBackslash,
+        Minus, Period, Quote, Semicolon, Slash,
+        AltLeft, AltRight, Backspace, CapsLock, ControlLeft, ControlRight, Enter,
+        ShiftLeft, ShiftRight, Space, Tab, Escape,
+        Delete, End, Home, Insert, PageDown, PageUp,
+        ArrowDown, ArrowLeft, ArrowRight, ArrowUp,
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8,
+        Numpad9, NumpadAdd, NumpadDecimal, NumpadDivide, NumpadEnter, NumpadMultiply,
+        NumpadSubtract,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, NumLock
+    ]
+};
+const MOUSE: [(MouseButton, &str); 5] = [
+    (MouseButton::Left, "MouseLeft"), (MouseButton::Right, "MouseRight"),
+    (MouseButton::Middle, "MouseMiddle"), (MouseButton::Back, "MouseBack"),
+    (MouseButton::Forward, "MouseForward")
+];
+// gamepad buttons go in the controls file as gilrs names them, after `Pad`.
+const BUTTONS: [Button; 19] = {
+    use Button::*;
+    [
+        South, East, North, West, C, Z,
+        LeftTrigger, LeftTrigger2, RightTrigger, RightTrigger2,
+        Select, Start, Mode, LeftThumb, RightThumb,
+        DPadUp, DPadDown, DPadLeft, DPadRight
+    ]
+};
+// the right stick isn't here since it always turns the camera.
+const STICKS: [(Axis, &str, &str); 2] = [
+    (Axis::LeftStickX, "PadLeftStickLeft", "PadLeftStickRight"),
+    (Axis::LeftStickY, "PadLeftStickDown", "PadLeftStickUp")
+];
+pub fn input_name(input: Bind) -> Option<String> {
+    match input {
+        Bind::Input(Input::Key(PhysicalKey::Code(key))) => KEYS.contains(&key).then(|| format!("{key:?}")),
+        Bind::Input(Input::Mouse(MouseButton::Other(i))) => Some(format!("Mouse{i}")),
+        Bind::Input(Input::Mouse(button)) => MOUSE.iter().find(|i| i.0 == button).map(|i| i.1.to_string()),
+        Bind::Input(_) => None,
+        Bind::Pad(PadInput::Button(button)) => BUTTONS.contains(&button).then(|| format!("Pad{button:?}")),
+        Bind::Pad(PadInput::Stick(axis, side)) => STICKS.iter()
+            .find(|i| i.0 == axis)
+            .map(|i| if side { i.2 } else { i.1 }.to_string())
+    }
+}
+pub fn parse_input(name: &str) -> Option<Bind> {
+    if let Some(key) = KEYS.iter().find(|i| format!("{i:?}") == name) { return Some(Bind::Input(Input::keycode(*key))) }
+    if let Some((button, _)) = MOUSE.iter().find(|i| i.1 == name) { return Some(Bind::Input(Input::Mouse(*button))) }
+    if let Some(button) = BUTTONS.iter().find(|i| format!("Pad{i:?}") == name) { return Some(Bind::Pad(PadInput::Button(*button))) }
+    if let Some((axis, left, _)) = STICKS.iter().find(|i| i.1 == name || i.2 == name) {
+        return Some(Bind::Pad(PadInput::Stick(*axis, name != *left)))
+    }
+    name.strip_prefix("Mouse").and_then(|i| i.parse().ok()).map(|i| Bind::Input(Input::Mouse(MouseButton::Other(i))))
+}
+// shorter names for the rebinding screen, `KeyW` is just `W`.
+pub fn short_name(input: Bind) -> String {
+    let name = input_name(input).unwrap_or("?".to_string());
+    ["Key", "Digit", "Arrow", "Pad"].iter()
+        .find_map(|i| name.strip_prefix(i))
+        .unwrap_or(&name).to_string()
+}
+// the action already using `input`, other than `action`. every input does one thing.
+pub fn conflict(binds: &Binds, action: Action, input: Bind) -> Option<Action> {
+    Action::ALL.into_iter().find(|i| *i != action && binds[*i as usize].contains(&input))
+}
+// the controls file has one action per line followed by its inputs, like
+//
+//   forward KeyW ArrowUp PadDPadUp PadLeftStickUp
+//
+// actions that aren't in the file keep their defaults. broken lines and inputs that
+// are already used by an earlier action are skipped with a warning.
+pub fn parse(text: &str) -> (Binds, Vec<String>) {
+    let mut binds = default_binds();
+    let mut warnings = vec![];
+    // the actions the file sets and the line each is on
+    let mut set: Vec<(Action, usize)> = vec![];
+    for (line, text) in text.lines().enumerate().map(|(i, text)| (i + 1, text.split("//").next().unwrap().trim())) {
+        let mut words = text.split_whitespace();
+        let Some(name) = words.next() else { continue };
+        let Some(action) = Action::ALL.into_iter().find(|i| i.name() == name) else {
+            warnings.push(format!("line {line}: unknown action {name}"));
+            continue
+        };
+        let mut inputs = vec![];
+        for word in words {
+            match parse_input(word) {
+                Some(input) if inputs.contains(&input) => (),
+                Some(input) => inputs.push(input),
+                None => warnings.push(format!("line {line}: unknown input {word}"))
+            }
+        }
+        if inputs.is_empty() {
+            warnings.push(format!("line {line}: {name} has no inputs, keeping the default"));
+            continue
+        }
+        binds[action as usize] = inputs;
+        set.push((action, line));
+    }
+    // every input does one thing. actions from the file claim their inputs before the
+    // defaults do, then earlier actions win.
+    let mut order = Action::ALL;
+    let line = |action: Action| set.iter().find(|i| i.0 == action).map(|i| i.1);
+    order.sort_by_key(|i| line(*i).is_none());
+    let mut claimed: Vec<(Bind, Action)> = vec![];
+    for action in order {
+        binds[action as usize].retain(|input| match claimed.iter().find(|i| i.0 == *input) {
+            Some((_, other)) => {
+                if let Some(line) = line(action) {
+                    let name = input_name(*input).unwrap_or_default();
+                    warnings.push(format!("line {line}: {name} is used by both {} and {}, keeping it on {}", other.name(), action.name(), other.name()));
+                }
+                false
+            },
+            None => {
+                claimed.push((*input, action));
+                true
+            }
+        });
+    }
+    for (action, inputs) in Action::ALL.iter().zip(&binds) {
+        if inputs.is_empty() { warnings.push(format!("{} has no inputs left", action.name())) }
+    }
+    (binds, warnings)
+}
+pub fn to_text(binds: &Binds) -> String {
+    let mut text = String::from("// each action followed by its inputs. keys use winit's KeyCode names and mouse\n// buttons are MouseLeft, MouseRight, MouseMiddle, MouseBack, MouseForward or Mouse<n>.\n// gamepad buttons are Pad then gilrs's Button name, and the left stick is\n// PadLeftStickUp, PadLeftStickDown, PadLeftStickLeft or PadLeftStickRight\n");
+    for (action, inputs) in Action::ALL.iter().zip(binds) {
+        let inputs: Vec<String> = inputs.iter().filter_map(|i| input_name(*i)).collect();
+        text += &format!("{} {}\n", action.name(), inputs.join(" "));
+    }
+    text
+}
+pub fn load(path: impl AsRef<Path>) -> Binds {
+    match fs::read_to_string(path) {
+        Ok(text) => {
+            let (binds, warnings) = parse(&text);
+            for i in warnings { println!("controls: {i}") }
+            binds
+        },
+        Err(i) if i.kind() == io::ErrorKind::NotFound => default_binds(),
+        Err(i) => {
+            println!("couldn't read controls: {i}");
+            default_binds()
+        }
+    }
+}
+pub fn save(binds: &Binds, path: impl AsRef<Path>) -> io::Result<()> {
+    if let Some(dir) = path.as_ref().parent() { fs::create_dir_all(dir)? }
+    fs::write(path, to_text(binds))
+}
+// the screen for changing controls. forward and back pick an action, right waits for
+// a new key, mouse or pad input to add to it and left removes its oldest one.
+#[derive(Default)]
+pub struct Rebinding {
+    pub selected: usize,
+    pub listening: bool,
+    // the action that already had the last input tried, shown for a moment.
+    pub conflict: Option<(Action, Instant)>
+}
+impl Rebinding {
+    // returns true when the screen is closed.
+    pub fn update(&mut self, binds: &mut Binds, input: &InputMap<ACTIONS>, pad: Option<PadInput>) -> bool {
+        let action = Action::ALL[self.selected];
+        if self.listening {
+            let Some(new) = pad.map(Bind::Pad).or(input.other_pressed.map(Bind::Input)) else { return false };
+            self.listening = false;
+            // escape always backs out so there's no getting stuck
+            if new == Bind::Input(Input::keycode(KeyCode::Escape)) || input_name(new).is_none() { return false }
+            match conflict(binds, action, new) {
+                Some(other) => self.conflict = Some((other, Instant::now())),
+                None => if !binds[self.selected].contains(&new) { binds[self.selected].push(new) }
+            }
+            return false
+        }
+        if input.pressed(Exit) || input.pressed(Rebind) { return true }
+        if input.pressed(Forward) { self.selected = (self.selected + ACTIONS - 1) % ACTIONS }
+        if input.pressed(Back) { self.selected = (self.selected + 1) % ACTIONS }
+        // waits for the input to come back up so that doesn't get taken as the new one
+        if input.released(Right) { self.listening = true }
+        if input.pressed(Left) && binds[self.selected].len() > 1 { binds[self.selected].remove(0); }
+        false
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn key(key: KeyCode) -> Bind {
+        Bind::Input(Input::keycode(key))
+    }
+    #[test]
+    fn defaults_survive_the_file() {
+        let (binds, warnings) = parse(&to_text(&default_binds()));
+        assert!(binds == default_binds());
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+    #[test]
+    fn files_set_their_actions() {
+        let (binds, warnings) = parse("// mine\npause KeyZ\n\nforward KeyW PadSouth PadLeftStickUp MouseLeft");
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(binds[Pause as usize], [key(KeyCode::KeyZ)]);
+        assert_eq!(binds[Forward as usize], [
+            key(KeyCode::KeyW), Bind::Pad(PadInput::Button(Button::South)),
+            Bind::Pad(PadInput::Stick(Axis::LeftStickY, true)), Bind::Input(Input::Mouse(MouseButton::Left))
+        ]);
+        // shrinking the map had the south button by default and lets it go
+        assert_eq!(binds[ShrinkMap as usize], [key(KeyCode::Minus)]);
+        assert_eq!(binds[Back as usize], default_binds()[Back as usize]);
+    }
+    #[test]
+    fn keys_go_to_the_first_action() {
+        let (binds, warnings) = parse("pause KeyZ\nstep KeyX KeyZ");
+        assert_eq!(warnings, ["line 2: KeyZ is used by both pause and step, keeping it on pause"]);
+        assert_eq!((&binds[Pause as usize][..], &binds[Step as usize][..]), (&[key(KeyCode::KeyZ)][..], &[key(KeyCode::KeyX)][..]));
+    }
+    #[test]
+    fn unknown_names_are_skipped() {
+        let (binds, warnings) = parse("pause KeyZ Flute\njump Space\nstep Flute");
+        assert_eq!(warnings, [
+            "line 1: unknown input Flute", "line 2: unknown action jump",
+            "line 3: unknown input Flute", "line 3: step has no inputs, keeping the default"
+        ]);
+        assert_eq!(binds[Pause as usize], [key(KeyCode::KeyZ)]);
+        assert_eq!(binds[Step as usize], default_binds()[Step as usize]);
+    }
+}
@@ -0,0 +1,208 @@
+use std::path::PathBuf;
+use snake3d_core::{Level, LevelError, Goal, Versus, AppleKind, MAX_PLAYERS, MAX_DELAY, MAX_EXTRAS, bot::Difficulty};
+use crate::controls::ControlMode;
+pub const USAGE: &str = "usage: snake [options]
+  --width <n>       board width, 2 to 255 (default 4)
+  --height <n>      board height, 2 to 255 (default 4)
+  --depth <n>       board depth, 2 to 255 (default 4)
+  --speed <n>       speed setting, 0 (fastest) to 6 (only move on input) (default 3)
+  --buffer <n>      turns that can be queued up between moves, 1 to 8 (default 3)
+  --seed <n>        seed every game with this instead of a random one
+  --wrap <axes>     axes that wrap round to the opposite face, any of x, y and z
+  --controls <mode> what the movement keys are relative to, world, camera or snake
+                    (default world)
+  --reverse         let the snake turn straight back into its neck
+  --players <n>     snakes on an empty board, 1 to 4 (default 1)
+  --opponents <n>   computer snakes to play against on an empty board, 0 to 3. players
+                    and opponents together can't be more than 4 (default 0)
+  --difficulty <d>  how well the opponents play, easy, medium or hard (default medium)
+  --versus <rule>   who wins with more than one snake, last (the last one left) or
+                    score (the most points once every snake is dead) (default last)
+  --fullscreen      start in fullscreen
+  --mute            start with sound off
+  --extras <n>      apples out as well as the usual one on an empty board, 0 to 16
+                    (default 0)
+  --kinds <kinds>   what the extra apples can be, any of normal, golden, shrinking,
+                    speed and timed split by commas (default normal)
+  --goal <goal>     what wins, one of perfect, length:<n>, apples:<n>, survive:<ticks>,
+                    reach:<x>,<y>,<z> or score:<n> (default perfect, or the level's goal)
+  --level <file>    play a level file instead of an empty board
+  --replay <file>   watch a replay file
+  --headless <n>    simulate n games with a bot and print statistics. online, the bot
+                    plays one game and prints how it ended
+  --bot <d>         the bot that plays for the players headless, easy, medium or hard
+                    (default easy)
+  --autopilot       start with the autopilot playing the first snake, press t to take
+                    over. headless it plays player one, winning every game on a board
+                    with no walls
+  --host <port>     host an online game, waiting for a player per snake past the first
+  --join <address>  join an online game at <ip>:<port>. the host picks the board
+  --delay <n>       ticks between a turn being taken and played online, 1 to 10,
+                    longer hides more lag (default 3)
+  --help            print this message";
+pub struct Options {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub speed: i8,
+    pub input_buffer: usize,
+    pub seed: Option<u64>,
+    pub wrap: [bool; 3],
+    pub goal: Option<Goal>,
+    pub reverse: bool,
+    pub players: usize,
+    pub opponents: usize,
+    pub difficulty: Difficulty,
+    pub versus: Option<Versus>,
+    pub extras: usize,
+    pub kinds: Vec<AppleKind>,
+    pub controls: ControlMode,
+    pub fullscreen: bool,
+    pub mute: bool,
+    pub level: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub headless: Option<usize>,
+    pub host: Option<u16>,
+    pub join: Option<String>,
+    pub delay: usize,
+    pub bot: Difficulty,
+    pub autopilot: bool,
+    pub help: bool
+}
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            width: 4, height: 4, depth: 4, speed: 3, input_buffer: 3, seed: None, wrap: [false; 3], goal: None, reverse: false,
+            players: 1, opponents: 0, difficulty: Difficulty::Medium, versus: None, extras: 0, kinds: vec![], controls: ControlMode::World,
+            fullscreen: false, mute: false, level: None, replay: None, headless: None,
+            host: None, join: None, delay: 3, bot: Difficulty::Easy, autopilot: false, help: false
+        }
+    }
+}
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+            match arg.as_str() {
+                "--width"  => options.width  = parse_range(&value()?, 2, 255, &arg)?,
+                "--height" => options.height = parse_range(&value()?, 2, 255, &arg)?,
+                "--depth"  => options.depth  = parse_range(&value()?, 2, 255, &arg)?,
+                "--speed"  => options.speed  = parse_range(&value()?, 0, 6, &arg)? as i8,
+                "--buffer" => options.input_buffer = parse_range(&value()?, 1, 8, &arg)?,
+                "--seed"   => options.seed   = Some(value()?.parse().map_err(|_| format!("invalid seed for {arg}"))?),
+                "--wrap"   => options.wrap   = parse_axes(&value()?, &arg)?,
+                "--controls" => options.controls = ControlMode::parse(&value()?)
+                    .ok_or(format!("{arg} must be world, camera or snake"))?,
+                "--players" => options.players = parse_range(&value()?, 1, MAX_PLAYERS, &arg)?,
+                "--opponents" => options.opponents = parse_range(&value()?, 0, MAX_PLAYERS - 1, &arg)?,
+                "--difficulty" => options.difficulty = Difficulty::parse(&value()?)
+                    .ok_or(format!("{arg} must be easy, medium or hard"))?,
+                "--bot" => options.bot = Difficulty::parse(&value()?)
+                    .ok_or(format!("{arg} must be easy, medium or hard"))?,
+                "--versus" => options.versus = Some(Versus::parse(&value()?).ok_or(format!("{arg} must be last or score"))?),
+                "--extras" => options.extras = parse_range(&value()?, 0, MAX_EXTRAS, &arg)?,
+                "--kinds"  => options.kinds = value()?.split(',').map(|i| AppleKind::parse(i)
+                    .ok_or(format!("{arg} takes normal, golden, shrinking, speed and timed, got {i}")))
+                    .collect::<Result<_, _>>()?,
+                "--goal"   => options.goal   = Some(parse_goal(&value()?, &arg)?),
+                "--level"  => options.level  = Some(value()?.into()),
+                "--replay" => options.replay = Some(value()?.into()),
+                "--headless" => options.headless = Some(parse_range(&value()?, 1, usize::MAX, &arg)?),
+                "--host"   => options.host   = Some(parse_range(&value()?, 1, u16::MAX as usize, &arg)? as u16),
+                "--join"   => options.join   = Some(value()?),
+                "--delay"  => options.delay  = parse_range(&value()?, 1, MAX_DELAY, &arg)?,
+                "--fullscreen" => options.fullscreen = true,
+                "--mute" => options.mute = true,
+                "--reverse" => options.reverse = true,
+                "--autopilot" => options.autopilot = true,
+                "--help" | "-h" => options.help = true,
+                _ => return Err(format!("unknown argument {arg}"))
+            }
+        }
+        if options.host.is_some() && options.join.is_some() { return Err("can't both --host and --join".into()) }
+        if options.players + options.opponents > MAX_PLAYERS {
+            return Err(format!("--players and --opponents add up to more than {MAX_PLAYERS} snakes"))
+        }
+        if options.opponents > 0 && (options.host.is_some() || options.join.is_some()) {
+            return Err("opponents can't play online".into())
+        }
+        if options.autopilot && (options.host.is_some() || options.join.is_some()) {
+            return Err("the autopilot can't play online".into())
+        }
+        Ok(options)
+    }
+    // the level file if one was given, otherwise an empty board of the chosen size.
+    pub fn level(&self) -> Result<Level, LevelError> {
+        let mut level = match &self.level {
+            Some(path) => Level::load(path)?,
+            None => self.empty_level([self.width, self.depth, self.height], self.wrap, self.players, Goal::Perfect)
+        };
+        if let Some(goal) = self.goal { level.goal = goal }
+        if let Some(versus) = self.versus { level.versus = versus }
+        Ok(level)
+    }
+    // an empty board with everything else these options set. the menu changes the size,
+    // wrap, players and goal as it goes so those are passed in.
+    pub fn empty_level(&self, dims: [usize; 3], wrap: [bool; 3], players: usize, goal: Goal) -> Level {
+        let mut level = Level::new(dims[0], dims[1], dims[2]);
+        (level.wrap, level.goal) = (wrap, goal);
+        level.set_players(players);
+        level.set_opponents(self.opponents, self.difficulty);
+        level.versus = self.versus.unwrap_or_default();
+        (level.extra_count, level.extra_kinds) = (self.extras, self.kinds.clone());
+        level
+    }
+}
+fn parse_range(value: &str, min: usize, max: usize, arg: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(i) if (min..=max).contains(&i) => Ok(i),
+        _ => Err(format!("{arg} must be a number from {min} to {max}, got {value}"))
+    }
+}
+fn parse_axes(value: &str, arg: &str) -> Result<[bool; 3], String> {
+    let mut axes = [false; 3];
+    for i in value.chars() {
+        match i {
+            'x' => axes[0] = true,
+            'y' => axes[1] = true,
+            'z' => axes[2] = true,
+            _ => return Err(format!("{arg} takes the axes x, y and z, got {value}"))
+        }
+    }
+    Ok(axes)
+}
+// the level syntax with `:` and `,` in place of spaces so it fits in one argument.
+fn parse_goal(value: &str, arg: &str) -> Result<Goal, String> {
+    let args: Vec<&str> = value.split([':', ',']).collect();
+    Goal::parse(&args).map_err(|i| format!("{arg}: {i}"))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snake3d_core::ivec3;
+    fn parse(args: &str) -> Result<Options, String> {
+        Options::parse(args.split_whitespace().map(String::from))
+    }
+    #[test]
+    fn flags_set_their_options() {
+        let options = parse("--width 5 --height 6 --depth 7 --wrap xz --goal reach:1,2,3 --seed 42").unwrap();
+        assert_eq!([options.width, options.height, options.depth], [5, 6, 7]);
+        assert_eq!(options.wrap, [true, false, true]);
+        assert_eq!(options.goal, Some(Goal::Reach(ivec3(1, 2, 3))));
+        assert_eq!(options.seed, Some(42));
+        assert_eq!(parse("--goal survive:30").unwrap().goal, Some(Goal::Survive(30)));
+        let options = parse("").unwrap();
+        assert_eq!((options.width, options.wrap, options.goal, options.seed), (4, [false; 3], None, None));
+    }
+    #[test]
+    fn bad_values_are_errors() {
+        for args in [
+            "--width 1", "--height 256", "--depth -3", "--depth four", "--width",
+            "--wrap xw", "--goal", "--goal sometimes", "--goal length:lots", "--goal reach:1,2",
+            "--seed -1", "--seed 1.5", "--size 4", "--players 3 --opponents 2"
+        ] {
+            assert!(parse(args).is_err(), "{args}");
+        }
+    }
+}
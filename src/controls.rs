@@ -0,0 +1,153 @@
+use thin_engine::prelude::*;
+use snake3d_core::Direction;
+// how the six movement keys pick a direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlMode {
+    // fixed to the board, forward is always +y.
+    World,
+    // relative to the camera, snapped to the closest axis.
+    Camera,
+    // relative to the snake's head. forward carries on, left, right, up and down turn.
+    // back carries on too, the snake can't turn round into itself.
+    Snake
+}
+impl ControlMode {
+    pub const ALL: [ControlMode; 3] = [ControlMode::World, ControlMode::Camera, ControlMode::Snake];
+    pub fn next(self) -> Self {
+        match self {
+            ControlMode::World  => ControlMode::Camera,
+            ControlMode::Camera => ControlMode::Snake,
+            ControlMode::Snake  => ControlMode::World
+        }
+    }
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.name() == text)
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlMode::World  => "world",
+            ControlMode::Camera => "camera",
+            ControlMode::Snake  => "snake"
+        }
+    }
+}
+fn to_vec(direction: Direction) -> Vec3 {
+    let dir = direction.dir();
+    vec3(dir.x as f32, dir.y as f32, dir.z as f32)
+}
+fn from_vec(value: snake3d_core::IVec3) -> Direction {
+    Direction::ALL.into_iter().find(|i| i.dir() == value).unwrap()
+}
+// the axis pointing the most along `value`, out of those not parallel to `used`.
+fn nearest(value: Vec3, used: &[Direction]) -> Direction {
+    Direction::ALL.into_iter()
+        .filter(|i| !used.iter().any(|used| used == i || used.opposite() == *i))
+        .max_by(|a, b| to_vec(*a).dot(value).total_cmp(&to_vec(*b).dot(value)))
+        .unwrap()
+}
+// the board directions closest to the screen's right, up and into the screen. the
+// rows of the camera's rotation are those axes in space, which is (x, z, y) on the board.
+// the view looks down +z so into the screen is the third row.
+pub fn camera_axes(camera_rot: Quaternion) -> (Direction, Direction, Direction) {
+    let rot = Mat4::from_rot(camera_rot);
+    let row = |i: usize| -> Vec3 {
+        let row = rot.row(i);
+        vec3(row[0], row[2], row[1])
+    };
+    let right = nearest(row(0), &[]);
+    let up = nearest(row(1), &[right]);
+    let forward = nearest(row(2), &[right, up]);
+    (right, up, forward)
+}
+// the direction a movement key means in `mode`. `pressed` is the key's board direction,
+// `heading` is where the snake will be going and `up` is the top of its head, which
+// turning up or down changes.
+pub fn resolve(
+    mode: ControlMode, pressed: Direction, camera_rot: Quaternion,
+    heading: Direction, up: &mut Direction
+) -> Direction {
+    let (right, top, forward) = match mode {
+        ControlMode::World => return pressed,
+        ControlMode::Camera => camera_axes(camera_rot),
+        ControlMode::Snake => {
+            if *up == heading || *up == heading.opposite() { *up = default_up(heading) }
+            let (h, u) = (heading.dir(), up.dir());
+            // heading cross up, x cross y being z on the board.
+            let right = snake3d_core::ivec3(h.y*u.z - h.z*u.y, h.z*u.x - h.x*u.z, h.x*u.y - h.y*u.x);
+            (from_vec(right), *up, heading)
+        }
+    };
+    let turn = match pressed {
+        Direction::Forward => forward,
+        Direction::Back if mode == ControlMode::Snake => forward,
+        Direction::Back    => forward.opposite(),
+        Direction::Right   => right,
+        Direction::Left    => right.opposite(),
+        Direction::Up      => top,
+        Direction::Down    => top.opposite()
+    };
+    // pitching the snake tips its head over
+    if mode == ControlMode::Snake {
+        if pressed == Direction::Up   { *up = heading.opposite() }
+        if pressed == Direction::Down { *up = heading }
+    }
+    turn
+}
+// the top of the snake's head when it isn't known.
+pub fn default_up(heading: Direction) -> Direction {
+    match heading {
+        Direction::Up | Direction::Down => Direction::Back,
+        _ => Direction::Up
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Direction::*;
+    // the camera as the game turns it, `x` round the board and `y` looking down on it.
+    fn camera(x: f32, y: f32) -> Quaternion {
+        Quaternion::from_x_rot(-y) * Quaternion::from_y_rot(-x)
+    }
+    fn all(mode: ControlMode, camera_rot: Quaternion, heading: Direction, up: Direction) -> Vec<Direction> {
+        Direction::ALL.iter().map(|i| resolve(mode, *i, camera_rot, heading, &mut up.clone())).collect()
+    }
+    #[test]
+    fn world_controls_ignore_the_camera() {
+        for rot in [camera(0.0, 0.4), camera(1.2, 1.0)] {
+            assert_eq!(all(ControlMode::World, rot, Left, Up), Direction::ALL);
+        }
+    }
+    #[test]
+    fn camera_controls_follow_the_view() {
+        // from the start, behind the board and a bit above it
+        assert_eq!(camera_axes(camera(0.0, 0.4)), (Right, Up, Forward));
+        assert_eq!(all(ControlMode::Camera, camera(0.0, 0.4), Left, Up), Direction::ALL);
+        // a quarter of the way round, into the screen is along x
+        assert_eq!(camera_axes(camera(std::f32::consts::FRAC_PI_2, 0.4)), (Back, Up, Right));
+        assert_eq!(all(ControlMode::Camera, camera(std::f32::consts::FRAC_PI_2, 0.4), Left, Up), [Right, Left, Forward, Back, Up, Down]);
+        // looking straight down, up the screen is forward
+        assert_eq!(camera_axes(camera(0.0, 1.4)), (Right, Forward, Down));
+    }
+    #[test]
+    fn snake_controls_turn_from_the_head() {
+        // heading forward with the head up, the same as the world
+        let mut up = Up;
+        assert_eq!(resolve(ControlMode::Snake, Left, camera(2.0, 0.4), Forward, &mut up), Left);
+        assert_eq!(resolve(ControlMode::Snake, Up, camera(2.0, 0.4), Forward, &mut up), Up);
+        // pitching up leaves the head facing back
+        assert_eq!(up, Back);
+        assert_eq!(all(ControlMode::Snake, camera(0.0, 0.4), Up, Back), [Up, Up, Left, Right, Back, Forward]);
+        assert_eq!(all(ControlMode::Snake, camera(0.0, 0.4), Right, Up), [Right, Right, Forward, Back, Up, Down]);
+    }
+    #[test]
+    fn snake_controls_never_turn_round() {
+        for heading in Direction::ALL {
+            for up in Direction::ALL {
+                for pressed in Direction::ALL {
+                    let turn = resolve(ControlMode::Snake, pressed, camera(0.5, 0.4), heading, &mut up.clone());
+                    assert_ne!(turn, heading.opposite(), "{pressed:?} heading {heading:?} with {up:?} up");
+                }
+            }
+        }
+    }
+}
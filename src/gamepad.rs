@@ -0,0 +1,160 @@
+use gilrs::{Axis, Button, EventType, Gilrs};
+use std::collections::VecDeque;
+use thin_engine::prelude::*;
+use crate::bindings::ACTIONS;
+// a gamepad button, or a stick pushed most of the way to one side. true is right or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadInput {
+    Button(Button),
+    Stick(Axis, bool)
+}
+// what a pad did. gilrs events get turned into these, and anything else can push them
+// too, so the pad controls work the same without a pad plugged in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadEvent {
+    Pressed(Button),
+    Released(Button),
+    Moved(Axis, f32),
+    Disconnected
+}
+// how far a stick goes to count as pressed, and how far back to count as released.
+// the gap stops it flickering when held near the edge.
+const PRESS: f32 = 0.6;
+const RELEASE: f32 = 0.4;
+pub struct Gamepad {
+    gilrs: Option<Gilrs>,
+    pub events: VecDeque<PadEvent>,
+    // the pad inputs for each action, in the order of `Action::ALL`.
+    pub binds: [Vec<PadInput>; ACTIONS],
+    held: Vec<PadInput>,
+    // the right stick, which turns the camera.
+    pub look: Vec2,
+    // the last input pressed this frame, for the rebinding screen.
+    pub pressed: Option<PadInput>
+}
+impl Gamepad {
+    pub fn new(binds: [Vec<PadInput>; ACTIONS]) -> Self {
+        // the game still works without pads so this isn't fatal
+        let gilrs = Gilrs::new().map_err(|i| println!("couldn't start gamepads: {i}")).ok();
+        Self::with_gilrs(binds, gilrs)
+    }
+    // without gilrs only the events pushed by hand do anything.
+    pub fn with_gilrs(binds: [Vec<PadInput>; ACTIONS], gilrs: Option<Gilrs>) -> Self {
+        Self { gilrs, events: VecDeque::new(), binds, held: vec![], look: Vec2::ZERO, pressed: None }
+    }
+    // applies what the pads did since last frame to `input`, as if they were keys.
+    pub fn update(&mut self, input: &mut InputMap<ACTIONS>) {
+        if let Some(gilrs) = &mut self.gilrs {
+            while let Some(event) = gilrs.next_event() {
+                self.events.push_back(match event.event {
+                    EventType::ButtonPressed(button, _)    => PadEvent::Pressed(button),
+                    EventType::ButtonReleased(button, _)   => PadEvent::Released(button),
+                    EventType::AxisChanged(axis, value, _) => PadEvent::Moved(axis, value),
+                    EventType::Disconnected => PadEvent::Disconnected,
+                    _ => continue
+                });
+            }
+        }
+        self.pressed = None;
+        while let Some(event) = self.events.pop_front() {
+            match event {
+                PadEvent::Pressed(button)  => self.press(PadInput::Button(button), input),
+                PadEvent::Released(button) => self.release(PadInput::Button(button), input),
+                PadEvent::Moved(Axis::RightStickX, value) => self.look.x = value,
+                PadEvent::Moved(Axis::RightStickY, value) => self.look.y = value,
+                PadEvent::Moved(axis, value) => for side in [true, false] {
+                    let value = if side { value } else { -value };
+                    if value >= PRESS { self.press(PadInput::Stick(axis, side), input) }
+                    else if value < RELEASE { self.release(PadInput::Stick(axis, side), input) }
+                },
+                PadEvent::Disconnected => {
+                    for i in self.held.clone() { self.release(i, input) }
+                    self.look = Vec2::ZERO;
+                }
+            }
+        }
+    }
+    fn press(&mut self, pad: PadInput, input: &mut InputMap<ACTIONS>) {
+        if self.held.contains(&pad) { return }
+        self.held.push(pad);
+        self.pressed = Some(pad);
+        for (i, binds) in self.binds.iter().enumerate() {
+            if !binds.contains(&pad) { continue }
+            input.pressed[i] |= !input.pressing[i];
+            input.pressing[i] = true;
+        }
+    }
+    fn release(&mut self, pad: PadInput, input: &mut InputMap<ACTIONS>) {
+        let Some(index) = self.held.iter().position(|i| *i == pad) else { return };
+        self.held.remove(index);
+        for (i, binds) in self.binds.iter().enumerate() {
+            // another input for the same action is still down
+            if !binds.contains(&pad) || binds.iter().any(|i| self.held.contains(i)) { continue }
+            input.released[i] |= input.pressing[i];
+            input.pressing[i] = false;
+        }
+    }
+}
+// pad events pushed by hand go through the same path as a real pad's.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::{self, Action::*};
+    fn pad() -> (Gamepad, InputMap<ACTIONS>) {
+        let binds = bindings::default_binds();
+        let pad = Gamepad::with_gilrs(bindings::pad_binds(&binds), None);
+        (pad, bindings::input_map(&binds))
+    }
+    #[test]
+    fn buttons_press_and_release_actions() {
+        let (mut pad, mut input) = pad();
+        pad.events.extend([PadEvent::Pressed(Button::DPadUp), PadEvent::Pressed(Button::North)]);
+        pad.update(&mut input);
+        assert!(input.pressed(Forward) && input.pressing(Forward) && input.pressed(SpeedUp));
+        assert_eq!(pad.pressed, Some(PadInput::Button(Button::North)));
+        input.pressed = [false; ACTIONS];
+        pad.events.push_back(PadEvent::Released(Button::DPadUp));
+        pad.update(&mut input);
+        assert!(input.released(Forward) && !input.pressing(Forward) && input.pressing(SpeedUp));
+    }
+    #[test]
+    fn sticks_press_past_the_threshold() {
+        let (mut pad, mut input) = pad();
+        pad.events.push_back(PadEvent::Moved(Axis::LeftStickX, -0.3));
+        pad.update(&mut input);
+        assert!(!input.pressing(Left));
+        pad.events.push_back(PadEvent::Moved(Axis::LeftStickX, -0.9));
+        pad.update(&mut input);
+        assert!(input.pressed(Left) && !input.pressing(Right));
+        // between the thresholds it stays held
+        input.pressed = [false; ACTIONS];
+        pad.events.push_back(PadEvent::Moved(Axis::LeftStickX, -0.5));
+        pad.update(&mut input);
+        assert!(input.pressing(Left) && !input.pressed(Left));
+        pad.events.push_back(PadEvent::Moved(Axis::LeftStickX, 0.0));
+        pad.update(&mut input);
+        assert!(input.released(Left));
+    }
+    #[test]
+    fn right_stick_only_turns_the_camera() {
+        let (mut pad, mut input) = pad();
+        pad.events.extend([PadEvent::Moved(Axis::RightStickX, 1.0), PadEvent::Moved(Axis::RightStickY, -0.5)]);
+        pad.update(&mut input);
+        assert_eq!(pad.look, vec2(1.0, -0.5));
+        assert!(!input.pressing.contains(&true));
+        pad.events.push_back(PadEvent::Disconnected);
+        pad.update(&mut input);
+        assert_eq!(pad.look, Vec2::ZERO);
+    }
+    #[test]
+    fn held_inputs_keep_an_action_down() {
+        let (mut pad, mut input) = pad();
+        pad.events.extend([PadEvent::Pressed(Button::DPadRight), PadEvent::Moved(Axis::LeftStickX, 1.0)]);
+        pad.events.push_back(PadEvent::Released(Button::DPadRight));
+        pad.update(&mut input);
+        assert!(input.pressing(Right) && !input.released(Right));
+        pad.events.push_back(PadEvent::Disconnected);
+        pad.update(&mut input);
+        assert!(input.released(Right) && !input.pressing(Right));
+    }
+}
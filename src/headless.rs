@@ -0,0 +1,81 @@
+use snake3d_core::{Autopilot, Batch, Controller, Death, Level, Replay, State, bot};
+use std::{thread, time::{Duration, Instant}};
+use crate::cli::Options;
+use crate::net::Session;
+// runs games with no window, a bot standing in for the players and the opponents playing
+// as they would in the game. with --autopilot it plays player one instead, which shows
+// a board can be filled.
+pub fn run(options: &Options, level: &Level, games: usize) {
+    let mut batch = Batch::new(level.clone(), games);
+    batch.seed = options.seed.unwrap_or_else(rand::random);
+    batch.speed = level.speed.unwrap_or(options.speed);
+    batch.allow_reverse = options.reverse;
+    // only the first snake gets the autopilot
+    let mut autopilot = options.autopilot.then(|| Autopilot::new(level.width, level.depth, level.height)).flatten();
+    let mut controllers: Vec<Box<dyn Controller>> = level.snakes.iter().map(|spawn| -> Box<dyn Controller> {
+        match (spawn.bot, autopilot.take()) {
+            (None, Some(autopilot)) => Box::new(autopilot),
+            (bot, _) => Box::new(bot.unwrap_or(options.bot))
+        }
+    }).collect();
+    let stats = batch.run(&mut controllers);
+    println!("games:          {games}");
+    println!("board:          {}x{}x{}", level.width, level.depth, level.height);
+    println!("goal:           {}", level.goal.to_text());
+    println!("wins:           {} ({:.1}%)", stats.wins, stats.win_rate() * 100.0);
+    println!("deaths:         {}", stats.losses);
+    println!("stuck:          {}", stats.stuck);
+    if stats.winners.len() > 1 {
+        let winners: Vec<String> = stats.winners.iter().map(|i| i.to_string()).collect();
+        println!("won by player:  {}", winners.join(" "));
+    }
+    println!("average length: {:.2}", stats.average_length());
+    println!("max length:     {}", stats.max_length());
+    println!("average ticks:  {:.1}", stats.average_ticks());
+    println!("average score:  {:.1}", stats.average_score());
+    println!("max score:      {}", stats.max_score);
+    println!("best combo:     {}", stats.best_combo);
+    // how many games ended at each length, and what player one died of
+    let lengths: Vec<String> = stats.lengths.iter().map(|(length, games)| format!("{length}:{games}")).collect();
+    println!("lengths:        {}", lengths.join(" "));
+    match stats.average_ticks_to_death() {
+        Some(ticks) => println!("ticks to death: {ticks:.1}"),
+        None => println!("ticks to death: never died")
+    }
+    let causes: Vec<String> = Death::ALL.iter().zip(stats.causes)
+        .filter(|i| i.1 > 0)
+        .map(|(death, games)| format!("{} {games}", death.name()))
+        .collect();
+    println!("died of:        {}", if causes.is_empty() { "nothing".into() } else { causes.join(", ") });
+}
+// plays one online game with the bot taking this player's turns, as fast as the other
+// players allow, then prints how it ended so every player's end can be compared.
+pub fn online(mut session: Session, game: Replay) {
+    let mut board = game.board();
+    let player = session.lockstep.player;
+    let cells = game.level.width * game.level.depth * game.level.height;
+    while board.state == State::Alive && board.ticks < cells * cells + 1000 {
+        if session.lockstep.needs_local() && board.snakes[player].alive {
+            session.turn(bot::greedy(&board, player));
+        }
+        match session.poll().and_then(|_| session.step(&mut board)) {
+            Ok(true) => (),
+            Ok(false) => thread::sleep(Duration::from_millis(1)),
+            Err(i) => {
+                println!("{i}");
+                break
+            }
+        }
+    }
+    // keep sending for a bit so the others get this player's last turns
+    let finished = Instant::now();
+    while finished.elapsed() < Duration::from_secs(1) {
+        let _ = session.poll();
+        thread::sleep(Duration::from_millis(10));
+    }
+    println!("player:         {}", player + 1);
+    println!("ticks:          {}", board.ticks);
+    println!("state:          {:?}", board.state);
+    println!("winner:         {}", board.winner.map_or("none".to_string(), |i| (i + 1).to_string()));
+    println!("board hash:     {:016x}", board.hash());
+}
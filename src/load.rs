@@ -2,7 +2,19 @@ use thin_engine::{
     prelude::*, glium::{uniforms::*, self},
     glium::texture::RawImage2d
 };
-use std::fs::File;
+use std::{fs::File, env, path::PathBuf};
+// where settings and progress are kept, e.g. ~/.config/snake-3d on linux.
+pub fn config_dir() -> PathBuf {
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|i| PathBuf::from(i).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|i| PathBuf::from(i).join(".config")))
+    };
+    base.unwrap_or_default().join("snake-3d")
+}
 pub fn sound(sound: &str) -> Result<Box<dyn awedio::Sound>, ()> {
     awedio::sounds::open_file(format!("sounds/{sound}.mp3")).map_err(|i| println!("{i}"))
 }
@@ -29,7 +41,13 @@ impl Mesh {
         let uvs: Vec<TextureCoords> = mesh.texcoords.chunks(2).map(|i|
             vec2(i[0], i[1]).into()
         ).collect();
-        let (index, vertex, normal, uv) = mesh!(display, &mesh.indices, &pos, &normals, &uvs);
+        Self::new(&mesh.indices, &pos, &normals, &uvs, display)
+    }
+    pub fn new(
+        indices: &[u32], pos: &[Vertex], normals: &[Normal],
+        uvs: &[TextureCoords], display: &thin_engine::Display
+    ) -> Self {
+        let (index, vertex, normal, uv) = mesh!(display, indices, pos, normals, uvs);
         Self { index, vertex, normal, uv }
     }
     pub fn mesh(&self) -> (&VertexBuffer<Vertex>, &VertexBuffer<Normal>, &VertexBuffer<TextureCoords>) {
@@ -66,6 +84,8 @@ pub fn shader(file: &str, screen_vertex: bool, display: &thin_engine::Display) -
     Program::from_source(display, vertex, &fragment, None).unwrap()
 }
 
+// the glyphs in font.png from the top down, each 3 by 5 with a row for the shadow.
+pub const FONT: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-?";
 pub struct ImageDrawer<'a> {
     pub screen_mesh: (&'a VertexBuffer<Vertex>, &'a VertexBuffer<TextureCoords>),
     pub screen_indices: &'a IndexBuffer<u32>,
@@ -91,4 +111,42 @@ impl ImageDrawer<'_> {
         let x = tex.width() as f32 / tex.height() as f32;
         self.draw(tex, pos, vec2(scale*x, scale), Vec2::ONE, Vec2::ZERO);
     }
+    // draws one frame of a vertical strip, frame 0 being the bottom one.
+    pub fn draw_frame(&mut self, tex: &Texture2d, frames: usize, frame: usize, pos: Vec2, scale: f32) {
+        let x = tex.width() as f32 / (tex.height() as f32 / frames as f32);
+        self.draw(
+            tex, pos, vec2(scale*x, scale),
+            vec2(1.0, 1.0 / frames as f32), vec2(0.0, frame as f32 / frames as f32)
+        );
+    }
+    // draws text in capitals starting at `pos`. spaces are gaps and anything not in the
+    // font comes out as `?`.
+    pub fn draw_text(&mut self, font: &Texture2d, text: &str, pos: Vec2, scale: f32) {
+        let count = FONT.len();
+        let char_width = scale * font.width() as f32 / (font.height() as f32 / count as f32 * 5.0 / 6.0);
+        let advance = char_width * 2.0 * 4.0 / 3.0;
+        for (i, char) in text.to_uppercase().chars().enumerate() {
+            if char == ' ' { continue }
+            let index = FONT.find(char).unwrap_or(count - 1);
+            let pos = vec2(pos.x + char_width + advance * i as f32, pos.y);
+            self.draw(
+                font, pos, vec2(char_width, scale * 6.0 / 5.0),
+                vec2(1.0, 1.0 / count as f32), vec2(0.0, (count - 1 - index) as f32 / count as f32)
+            );
+        }
+    }
+    // draws a number centred on `pos` using a vertical strip of the 10 digits.
+    pub fn draw_number(&mut self, digits: &Texture2d, number: u64, pos: Vec2, scale: f32) {
+        let number = number.to_string();
+        let char_width = scale * digits.width() as f32 / (digits.height() as f32 / 10.0);
+        let advance = char_width * 2.0 * 4.0 / 3.0;
+        let start = pos.x - advance * (number.len() - 1) as f32 / 2.0;
+        for (i, digit) in number.bytes().enumerate() {
+            let digit = (digit - b'0') as f32;
+            self.draw(
+                digits, vec2(start + advance * i as f32, pos.y),
+                vec2(char_width, scale), vec2(1.0, 0.1), vec2(0.0, digit / 10.0)
+            );
+        }
+    }
 }
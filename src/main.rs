@@ -5,40 +5,65 @@ use thin_engine::{
     ResizableTexture2D,
     ResizableDepthTexture2D,
 };
-use crate::snake::*;
+use snake3d_core::{Direction, State, Replay, ReplayPlayer, Campaign, Goal, HighScores, ScoreEntry, Board, Autopilot, AppleKind, MAX_PLAYERS};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::render::*;
 use crate::load::*;
+use crate::cli::Options;
+use crate::controls::ControlMode;
 use awedio::Sound;
 mod load;
-mod snake;
-#[derive(ToUsize)]
-enum Action {
-    Up, Down, Left, Right, Forward, Back,
-    ExpandMap, ShrinkMap, SpeedUp, SlowDown,
-    ToggleFullscreen, Exit, Mute
-}
-use Action::*;
+mod render;
+mod cli;
+mod headless;
+mod controls;
+mod bindings;
+mod gamepad;
+mod net;
+use bindings::{Action::*, ACTIONS, MOVES, Rebinding};
 fn main() {
-    let mut input = input_map!(
-        (Exit,             KeyCode::Escape),
-        (ToggleFullscreen, KeyCode::KeyF),
-        (Mute,             KeyCode::KeyM),
-        (SpeedUp,          KeyCode::Digit1),
-        (SlowDown,         KeyCode::Digit2),
-        (ExpandMap,        KeyCode::Equal),
-        (ShrinkMap,        KeyCode::Minus),
-        (Forward,          KeyCode::KeyW, KeyCode::ArrowUp),
-        (Back,             KeyCode::KeyS, KeyCode::ArrowDown),
-        (Down,             KeyCode::KeyQ, KeyCode::Enter),
-        (Up,               KeyCode::KeyE, KeyCode::Space),
-        (Left,             KeyCode::KeyA, KeyCode::ArrowLeft),
-        (Right,            KeyCode::KeyD, KeyCode::ArrowRight)
-    );
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(i) => i,
+        Err(i) => {
+            println!("{i}\n{}", cli::USAGE);
+            std::process::exit(1)
+        }
+    };
+    if options.help { println!("{}", cli::USAGE); return }
+    let mut level = match options.level() {
+        Ok(i) => i,
+        Err(i) => {
+            println!("{i}");
+            std::process::exit(1)
+        }
+    };
+    // online games are joined before the window opens so it opens on the game
+    let online = match net::connect(&options, &level) {
+        Ok(i) => i,
+        Err(i) => {
+            println!("{i}");
+            std::process::exit(1)
+        }
+    };
+    if let Some(games) = options.headless {
+        match online {
+            Some((session, game)) => headless::online(session, game),
+            None => headless::run(&options, &level, games)
+        }
+        return
+    }
+
+    let controls_path = config_dir().join("controls.txt");
+    let mut binds = bindings::load(&controls_path);
+    let mut input = bindings::input_map(&binds);
+    let mut gamepad = gamepad::Gamepad::new(bindings::pad_binds(&binds));
 
     //create window
     let (event_loop, window, display) = thin_engine::set_up().unwrap();
     window.set_cursor_visible(false);
     window.set_title("Snake 3D");
     let _ = window.set_cursor_grab(CursorGrabMode::Locked);
+    if options.fullscreen { window.set_fullscreen(Some(Fullscreen::Borderless(None))) }
     
     // try to create audio
     let backend = awedio::start().map_err(|i| println!("{i}"));
@@ -60,6 +85,8 @@ fn main() {
         Ok((a, b)) => (Ok(a), Ok(b)),
         Err(a) => (Err(a), Err(a))
     };
+    let mut play_sounds = !options.mute;
+    if let Ok(ref mut i) = music { i.set_paused(!play_sounds) }
     play_sound(music_sound, manager);
 
     let eat_sound = sound("eat")
@@ -67,6 +94,15 @@ fn main() {
             .into_memory_sound()
             .map_err(|i| println!("{i}")
     ));
+    // each kind of apple can have its own sound in sounds/eat_<kind>.mp3, otherwise it
+    // gets the usual one
+    let apple_sounds = AppleKind::ALL.map(|kind| {
+        let name = format!("eat_{}", kind.name());
+        match kind != AppleKind::Normal && std::path::Path::new(&format!("sounds/{name}.mp3")).exists() {
+            true => sound(&name).and_then(|i| i.into_memory_sound().map_err(|i| println!("{i}"))),
+            false => eat_sound.clone()
+        }
+    });
     let ow_sound  = sound("ow")
         .and_then(|i| i
             .into_memory_sound()
@@ -81,14 +117,21 @@ fn main() {
     let start_tex    = image("start",    &display);
     let re_tex       = image("re",       &display);
     let win_tex      = image("win",      &display);
+    let digits_tex   = image("digits",   &display);
+    let wrap_tex     = image("wrap",     &display);
+    let goals_tex    = image("goals",    &display);
+    let best_tex     = image("best",     &display);
+    let modes_tex    = image("modes",    &display);
+    let font_tex     = image("font",     &display);
+    let players_tex  = image("players",  &display);
     let mut depth  = ResizableDepthTexture2D::default();
     let mut colour = ResizableTexture2D::default();
 
     // load meshes
     let apple  = Mesh::load("apple", &display);
     let snake  = Mesh::load("snake", &display);
+    let wall   = Mesh::load("wall",  &display);
     let face   = Mesh::load("face",  &display);
-    let board  = Mesh::load("board", &display);
     let (screen_indices, screen_vertices, screen_uvs) = mesh!(
         &display, &screen::INDICES, &screen::VERTICES, &screen::UVS
     );
@@ -108,21 +151,87 @@ fn main() {
         backface_culling: glium::BackfaceCullingMode::CullCounterClockwise,
         ..Default::default()
     };
+    let ghost_parameters = DrawParameters{
+        blend: draw_parameters::Blend::alpha_blending(),
+        ..params::alias_3d()
+    };
 
     // create game
-    let mut size = 4;
-    let mut rng = rand::thread_rng();
-    let mut game = Board::new(size, size, size);
-    game.state = State::Wait;
+    // width, depth and height. `axis` picks which one the map controls change, 3 is all.
+    let mut dims = [level.width, level.depth, level.height];
+    let mut axis = 3;
+    let mut wrap = level.wrap;
+    let mut goal_kind = level.goal.index();
+    // the snakes people play, the computer's come after
+    let mut players = level.snakes.iter().filter(|i| i.bot.is_none()).count();
+    let new_seed = || options.seed.unwrap_or_else(rand::random);
+    let seed = new_seed();
+
+    // the campaign is optional, the game still works with just empty boards.
+    let progress_path = config_dir().join("campaign.txt");
+    let mut campaign = Campaign::load("levels").map_err(|i| println!("{i}")).ok();
+    if let Some(campaign) = &mut campaign {
+        let _ = campaign.load_progress(&progress_path);
+    }
+    // the campaign level being played, if playing the campaign.
+    let mut campaign_level: Option<usize> = None;
+    // the size, wrap and goal of the free play board to go back to after the campaign,
+    // starting with the ones given on the command line.
+    let mut free_play = (dims, wrap, level.goal);
+
+    let scores_path = config_dir().join("highscores.txt");
+    let mut high_scores = HighScores::load(&scores_path).unwrap_or_default();
+    // where the last game placed in its table, 0 being a new record.
+    let mut new_record: Option<usize> = None;
 
     let mut prev_dir = Direction::Forward.dir();
-    let mut prev_length = 1;
+    let mut prev_apples = 0;
+    let mut prev_alive = players;
     let mut cam_rot = vec2(0.0, 0.4);
-    let mut play_sounds = true;
 
     // create time
-    let mut speed = 3;
-    let mut fixed_loop_timer = 0.4;
+    let mut speed = level.speed.unwrap_or(options.speed);
+    let mut replay = Replay::new(level.clone(), seed, speed);
+    replay.allow_reverse = options.reverse;
+    let mut game = replay.board();
+    for i in &mut game.snakes { i.queue_depth = options.input_buffer }
+    game.state = State::Wait;
+    let mut control_mode = options.controls;
+    let mut rebinding: Option<Rebinding> = None;
+    // the top of each snake's head, for snake relative controls
+    let mut snake_ups = default_ups(&game);
+    let mut player: Option<ReplayPlayer> = None;
+    let mut paused = false;
+    let mut session = None;
+    // plays the first snake while it's on. games it played a part in don't count for
+    // high scores or the campaign
+    let mut autopilot = options.autopilot.then(|| Autopilot::new(level.width, level.depth, level.height)).flatten();
+    let mut piloted = false;
+    if let Some((new_session, online_replay)) = online {
+        (replay, session) = (online_replay, Some(new_session));
+        game = replay.board();
+        snake_ups = default_ups(&game);
+        prev_alive = game.snakes.len();
+        level = replay.level.clone();
+        (dims, wrap, players) = ([level.width, level.depth, level.height], level.wrap, level.snakes.len());
+    }
+    if let Some(path) = &options.replay {
+        match Replay::load(path) {
+            Ok(loaded) => {
+                let (new_player, board) = ReplayPlayer::new(loaded.clone());
+                (replay, game, player) = (loaded, board, Some(new_player));
+                prev_alive = game.snakes.len();
+                level = replay.level.clone();
+                (dims, wrap) = ([level.width, level.depth, level.height], level.wrap);
+            },
+            Err(i) => println!("couldn't load replay {}: {i}", path.display())
+        }
+    }
+    // online games can't wait for input so they play like a replay
+    let mut fixed_loop_timer = match player.is_some() || session.is_some() {
+        true => playback_timer(replay.speed),
+        false => speed_timer(speed)
+    };
     let mut fixed_loop = Instant::now();
     let menu_loop_timer = 0.17;
     let mut menu_loop = Instant::now();
@@ -130,6 +239,9 @@ fn main() {
     let mut delta = 0.0;
 
     let (mut apple_mat, mut snake_parts_mat, mut shadows_mat) = game.matrices();
+    let mut walls_mat = game.wall_matrices();
+    let mut board = board_mesh(dims[0], dims[1], dims[2], &display);
+    let mut board_dims = dims;
     thin_engine::run(event_loop, &mut input, |input, target| {
         let elapsed = Instant::now();
         let screen_size = window.inner_size().into();
@@ -145,6 +257,21 @@ fn main() {
         let view2d = Mat4::view_matrix_2d(screen_size);
 
         let menu_timer_looped = menu_loop.elapsed().as_secs_f32() >= menu_loop_timer;
+        gamepad.update(input);
+
+        // the rebinding screen takes every input while it's open
+        if let Some(screen) = &mut rebinding {
+            if screen.update(&mut binds, input, gamepad.pressed) {
+                rebinding = None;
+                let _ = bindings::save(&binds, &controls_path).map_err(|i| println!("{i}"));
+            }
+            input.binds = bindings::key_binds(&binds);
+            gamepad.binds = bindings::pad_binds(&binds);
+            (input.pressed, input.pressing) = ([false; ACTIONS], [false; ACTIONS]);
+        } else if game.state != State::Alive && input.pressed(Rebind) {
+            rebinding = Some(Rebinding::default());
+            input.pressed = [false; ACTIONS];
+        }
 
         // controls
         if input.pressed(ToggleFullscreen) {
@@ -161,12 +288,68 @@ fn main() {
             }
         }
 
+        if let (Some(campaign), true) = (&campaign, game.state != State::Alive && input.pressed(ToggleCampaign)) {
+            campaign_level = match campaign_level {
+                None => {
+                    free_play = (dims, wrap, level.goal);
+                    Some(campaign.furthest())
+                },
+                Some(_) => None
+            };
+            if let Some(index) = campaign_level {
+                level = campaign.levels[index].clone();
+                speed = level.speed.unwrap_or(speed);
+            } else {
+                let (dims, wrap, goal) = free_play;
+                level = options.empty_level(dims, wrap, players, goal);
+                goal_kind = goal.index();
+            }
+            (dims, wrap) = ([level.width, level.depth, level.height], level.wrap);
+        }
+        if game.state != State::Alive && campaign_level.is_none() {
+            if input.pressed(CycleGoal) {
+                goal_kind = (goal_kind + 1) % Goal::KINDS.len();
+                level.goal = free_goal(goal_kind, dims);
+            }
+            if input.pressed(CyclePlayers) {
+                players = players % (MAX_PLAYERS - options.opponents) + 1;
+                level.set_players(players);
+                level.set_opponents(options.opponents, options.difficulty);
+            }
+            if input.pressed(SelectAxis) { axis = (axis + 1) % 4 }
+            if input.pressed(ToggleWrap) {
+                if axis == 3 { wrap = [!wrap.iter().all(|i| *i); 3] }
+                else { wrap[axis] = !wrap[axis] }
+            }
+        }
         let changed_map = input.pressed(ExpandMap) || input.pressed(ShrinkMap);
         if game.state != State::Alive && (menu_timer_looped || changed_map) {
             let change = input.axis(ShrinkMap, ExpandMap) as i32;
-            size = 2.max(size as i32 - change).min(255) as usize;
+            match (&campaign, &mut campaign_level) {
+                // in the campaign the map controls pick an unlocked level instead
+                (Some(campaign), Some(index)) => {
+                    let new_index = (*index as i32 - change).max(0) as usize;
+                    if change != 0 && campaign.unlocked(new_index) {
+                        *index = new_index;
+                        level = campaign.levels[new_index].clone();
+                        speed = level.speed.unwrap_or(speed);
+                        (dims, wrap) = ([level.width, level.depth, level.height], level.wrap);
+                    }
+                },
+                _ => for (i, size) in dims.iter_mut().enumerate() {
+                    if axis == 3 || axis == i { *size = 2.max(*size as i32 - change).min(255) as usize }
+                }
+            }
             if change != 0 { menu_loop = Instant::now(); }
         }
+        // changing the map goes back to an empty board
+        if campaign_level.is_none() && (dims != [level.width, level.depth, level.height] || wrap != level.wrap) {
+            let goal = match level.goal {
+                Goal::Reach(_) => free_goal(goal_kind, dims),
+                goal => goal
+            };
+            level = options.empty_level(dims, wrap, players, goal);
+        }
         
         let changed_speed = input.pressed(SpeedUp) || input.pressed(SlowDown);
         if game.state != State::Alive && (changed_speed || menu_timer_looped) {
@@ -177,49 +360,211 @@ fn main() {
         }
 
         if input.pressed(Exit) { target.exit() }
+        if input.pressed(CycleControls) { control_mode = control_mode.next() }
+        if input.pressed(ToggleAutopilot) && player.is_none() && session.is_none() {
+            autopilot = match autopilot {
+                Some(_) => None,
+                None => Autopilot::new(replay.level.width, replay.level.depth, replay.level.height)
+            };
+            // turns still waiting from the player would steer it off the cycle
+            if autopilot.is_some() { game.snakes[0].queue.clear() }
+        }
 
-        let mut move_input = true;
-        if input.pressed(Forward)    { game.snake.direction = Direction::Forward }
-        else if input.pressed(Back)  { game.snake.direction = Direction::Back    }
-        else if input.pressed(Left)  { game.snake.direction = Direction::Left    }
-        else if input.pressed(Right) { game.snake.direction = Direction::Right   }
-        else if input.pressed(Up)    { game.snake.direction = Direction::Up      }
-        else if input.pressed(Down)  { game.snake.direction = Direction::Down    }
-        else { move_input = false }
+        // every direction pressed this frame is queued for each player, so quick turns
+        // aren't lost
+        let pressed_dirs: Vec<Vec<Direction>> = MOVES.iter().map(|moves| {
+            let directions = [Direction::Up, Direction::Down, Direction::Left, Direction::Right, Direction::Forward, Direction::Back];
+            moves.iter().zip(directions).filter(|i| input.pressed(*i.0)).map(|i| i.1).collect()
+        }).collect();
+        let move_input = pressed_dirs.iter().any(|i| !i.is_empty());
 
         // reset
         if game.state != State::Alive && move_input && menu_timer_looped {
-            game = Board::new(size, size, size);
+            // a watched replay may have swapped the level out
+            if let (Some(campaign), Some(index)) = (&campaign, campaign_level) {
+                level = campaign.levels[index].clone();
+                (dims, wrap) = ([level.width, level.depth, level.height], level.wrap);
+            }
+            let seed = new_seed();
+            fixed_loop_timer = speed_timer(speed);
+            replay = Replay::new(level.clone(), seed, speed);
+            replay.allow_reverse = options.reverse;
+            game = replay.board();
+            for i in &mut game.snakes { i.queue_depth = options.input_buffer }
+            snake_ups = default_ups(&game);
+            (prev_apples, prev_alive) = (0, game.snakes.len());
+            new_record = None;
+            (player, session) = (None, None);
+            if autopilot.is_some() { autopilot = Autopilot::new(level.width, level.depth, level.height) }
+            piloted = false;
+            walls_mat = game.wall_matrices();
+        }
+        let camera_rot = Quaternion::from_x_rot(-cam_rot.y) * Quaternion::from_y_rot(-cam_rot.x);
+        if let Some(session) = &mut session {
+            // online the first player's keys turn this player's snake
+            let snake_up = &mut snake_ups[session.lockstep.player];
+            for i in &pressed_dirs[0] {
+                let mut up = *snake_up;
+                let queued = session.heading();
+                session.turn(controls::resolve(control_mode, *i, camera_rot, queued, &mut up));
+                if session.heading() != queued { *snake_up = up }
+            }
+        } else if player.is_none() {
+            for (i, pressed) in pressed_dirs.into_iter().enumerate().take(game.snakes.len()) {
+                // the computer's snakes don't take keys, nor does one on autopilot
+                if replay.level.snakes[i].bot.is_some() || (i == 0 && autopilot.is_some()) { continue }
+                let (snake, snake_up) = (&mut game.snakes[i], &mut snake_ups[i]);
+                for i in pressed {
+                    let heading = snake.queue.back().copied().unwrap_or(snake.direction);
+                    let mut up = *snake_up;
+                    let queued = snake.queue.len();
+                    snake.turn(controls::resolve(control_mode, i, camera_rot, heading, &mut up));
+                    // the head only tips over if the turn was taken
+                    if snake.queue.len() > queued { *snake_up = up }
+                }
+            }
+        }
+
+        // watch the last game again
+        if game.state != State::Alive && game.state != State::Wait && input.pressed(WatchReplay) {
+            let (new_player, board) = ReplayPlayer::new(replay.clone());
+            level = replay.level.clone();
+            (dims, wrap) = ([level.width, level.depth, level.height], level.wrap);
+            fixed_loop_timer = playback_timer(replay.speed);
+            (game, player, paused) = (board, Some(new_player), false);
+            (apple_mat, snake_parts_mat, shadows_mat) = game.matrices();
+            walls_mat = game.wall_matrices();
+            (prev_apples, prev_alive) = (apples_eaten(&game), game.alive());
+        }
+
+        // replay controls
+        let mut stepped = false;
+        if let Some(player) = &mut player {
+            if input.pressed(Pause) { paused = !paused }
+            if input.pressed(Step) && paused {
+                player.step(&mut game);
+                stepped = true;
+            }
+            if input.pressing(Rewind) && menu_timer_looped {
+                player.rewind(&mut game, 1);
+                menu_loop = Instant::now();
+                stepped = true;
+            }
+            if stepped {
+                (apple_mat, snake_parts_mat, shadows_mat) = game.matrices();
+                (prev_apples, prev_alive) = (apples_eaten(&game), game.alive());
+            }
         }
         
         cam_rot += input.mouse_move.scale(delta);
+        // the stick turns at a steady speed, pushing up looks from above
+        cam_rot += vec2(gamepad.look.x, -gamepad.look.y).scale(delta * 3.0);
 
         //update game every `fixed_loop_timer` seconds
-        if (fixed_loop.elapsed().as_secs_f32() >= fixed_loop_timer || move_input) && game.state == State::Alive {
+        let loop_timer = if input.pressing(FastForward) && player.is_some() { fixed_loop_timer / 4.0 } else { fixed_loop_timer };
+        // a speed apple hurries the game along for a while
+        let loop_timer = if game.haste > 0 { loop_timer / 2.0 } else { loop_timer };
+        let ticked = match player {
+            Some(_) => !paused && fixed_loop.elapsed().as_secs_f32() >= loop_timer,
+            // the slowest speed only moves on input
+            None => fixed_loop.elapsed().as_secs_f32() >= loop_timer || (move_input && loop_timer.is_infinite())
+        };
+        // online games also wait for every player's turn to come in, and the session plays
+        // the tick instead of `update`. the session is kept after the game ends so the
+        // others still get this player's last turns.
+        let mut play = ticked && game.state == State::Alive;
+        if let Some(online) = &mut session {
+            let stepped = online.poll().and_then(|_| match play {
+                true => online.step(&mut game),
+                false => Ok(false)
+            });
+            match stepped {
+                Ok(stepped) => play = stepped,
+                Err(i) => {
+                    if game.state == State::Alive {
+                        println!("{i}");
+                        game.state = State::Dead;
+                    }
+                    session = None;
+                    play = false;
+                }
+            }
+        }
+        if play {
             fixed_loop = Instant::now();
-            game.update(&mut rng);
+            if let Some(player) = &mut player {
+                player.step(&mut game);
+            } else {
+                if session.is_none() {
+                    if let (Some(autopilot), true) = (&autopilot, game.snakes[0].alive && replay.level.snakes[0].bot.is_none()) {
+                        // set straight away like `Batch` does, the autopilot picks every tick
+                        game.snakes[0].direction = autopilot.pick(&game, 0);
+                        piloted = true;
+                    }
+                    // the computer's snakes turn the same way a player's do
+                    for (i, spawn) in replay.level.snakes.iter().enumerate() {
+                        if let (Some(bot), true) = (spawn.bot, game.snakes[i].alive) {
+                            let direction = bot.pick(&game, i);
+                            game.snakes[i].turn(direction);
+                        }
+                    }
+                    game.update();
+                }
+                replay.record(&game);
+                if game.state != State::Alive {
+                    let _ = replay.save("replays/last.s3dr").map_err(|i| println!("{i}"));
+                }
+                // high scores are only kept for games on your own
+                if game.state != State::Alive && game.snakes.len() == 1 && !piloted {
+                    // scores that make the table keep their replay next to it
+                    let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |i| i.as_secs());
+                    let replay_path = config_dir().join("replays").join(format!("{time}-{}.s3dr", replay.seed));
+                    let entry = ScoreEntry {
+                        points: game.snakes[0].score.points, date: snake3d_core::date(time),
+                        seed: replay.seed, replay: replay_path.display().to_string()
+                    };
+                    new_record = high_scores.insert(&snake3d_core::score_key(&replay.level, replay.speed), entry);
+                    if new_record.is_some() {
+                        let _ = replay.save(&replay_path).map_err(|i| println!("{i}"));
+                        let _ = high_scores.save(&scores_path).map_err(|i| println!("{i}"));
+                    }
+                }
+                // beating a campaign level unlocks the next one, which the next move starts
+                if let (State::Win, false, Some(campaign), Some(index)) = (game.state, piloted, &mut campaign, &mut campaign_level) {
+                    campaign.complete(*index);
+                    let _ = campaign.save_progress(&progress_path).map_err(|i| println!("{i}"));
+                    if *index + 1 < campaign.levels.len() {
+                        *index += 1;
+                        level = campaign.levels[*index].clone();
+                        speed = level.speed.unwrap_or(speed);
+                        (dims, wrap) = ([level.width, level.depth, level.height], level.wrap);
+                    }
+                }
+            }
             (apple_mat, snake_parts_mat, shadows_mat) = game.matrices();
 
-            if prev_length != game.snake_body.len() && play_sounds { 
-                play_sound(eat_sound.clone(), manager);
+            if prev_apples != apples_eaten(&game) && play_sounds { 
+                for i in &game.eaten { play_sound(apple_sounds[*i as usize].clone(), manager) }
             }
-            if game.state == State::Dead && play_sounds { 
+            if prev_alive != game.alive() && play_sounds { 
                 play_sound(ow_sound.clone(), manager);
             }
-            prev_length = game.snake_body.len();
-            prev_dir = game.snake.direction.dir();
+            (prev_apples, prev_alive) = (apples_eaten(&game), game.alive());
+            prev_dir = game.snakes[0].direction.dir();
         }
         
+        if board_dims != dims {
+            board = board_mesh(dims[0], dims[1], dims[2], &display);
+            board_dims = dims;
+        }
+
         // aplied in inverse order because vertex shader takes inverse camera.
-        // simplify (Vec3::splat(size as f32)).length() to size* sqrt(3)
+        // far enough back to see the whole board from any angle.
+        let board_extent = vec3(dims[0] as f32, dims[2] as f32, dims[1] as f32);
         let camera = Mat4::from_pos_and_rot(
-            vec3(0.0, 0.0, size as f32*3.0_f32.sqrt()),
+            vec3(0.0, 0.0, board_extent.length()),
             Quaternion::from_x_rot(-cam_rot.y) * Quaternion::from_y_rot(-cam_rot.x)
         );
-        let board_transform = Mat4::from_scale_and_rot(
-            Vec3::splat(size as f32),
-            Quaternion::from_y_rot(std::f32::consts::PI/2.0)
-        );
         let (width, height) = frame.get_dimensions();
         let right_side = width as f32 / height as f32;
 
@@ -240,10 +585,10 @@ fn main() {
         frame.draw(
             board.mesh(), board.index(), &image_shader,
             &uniform! {
-                camera: camera, model: board_transform,
+                camera: camera, model: Mat4::default(),
                 tex: sampler(&board_tex),
                 view: view, offset: Vec2::ZERO,
-                size: Vec2::splat(size as f32)
+                size: Vec2::splat(0.5)
             },
             &image_parameters
         ).unwrap();
@@ -259,43 +604,87 @@ fn main() {
             ).unwrap()
         });
 
-        //draw face
-        let face_pos = game.board_to_space(game.snake.pos);
-        let face_rot = game.snake.direction.rot();
-        frame.draw(
-            face.mesh(), face.index(), &image_shader,
-            &uniform! {
-                tex: sampler(&face_tex),
-                camera: camera, view: view,
-                model: Mat4::from_pos_and_rot(face_pos, face_rot),
-                size: Vec2::ONE, offset: Vec2::ZERO,
-            }, 
-            &mesh_parameters
-        ).unwrap();
+        //draw faces. with more than one snake the dead ones are see through
+        let faded = |colour: Vec4| vec4(colour.x, colour.y, colour.z, 0.3);
+        for (i, (parts, snake_state)) in snake_parts_mat.iter().zip(&game.snakes).enumerate() {
+            let [albedo, shadow, specular] = SNAKE_COLOURS[i];
+            let solid = snake_state.alive || game.snakes.len() == 1;
+            if solid {
+                let face_pos = game.board_to_space(grid(snake_state.pos));
+                frame.draw(
+                    face.mesh(), face.index(), &image_shader,
+                    &uniform! {
+                        tex: sampler(&face_tex),
+                        camera: camera, view: view,
+                        model: Mat4::from_pos_and_rot(face_pos, snake_state.direction.rot()),
+                        size: Vec2::ONE, offset: Vec2::ZERO,
+                    }, 
+                    &mesh_parameters
+                ).unwrap();
+            }
+
+            //draw snake
+            let (albedo, shadow, specular, parameters) = match solid {
+                true => (albedo, shadow, specular, &mesh_parameters),
+                false => (faded(albedo), faded(shadow), faded(specular), &ghost_parameters)
+            };
+            parts.iter().for_each(|i| frame.draw(
+                snake.mesh(), snake.index(), &shaded_shader,
+                &uniform! { camera: camera, model: *i, view: view, albedo: albedo, shadow: shadow, specular: specular },
+                parameters
+            ).unwrap());
+        }
 
-        //draw snake
-        snake_parts_mat.iter().for_each(|i| frame.draw(
-            snake.mesh(), snake.index(), &shaded_shader,
+        //draw where the snakes come out of wrapping faces
+        if game.wrap.contains(&true) { for (i, ghosts) in game.ghost_matrices().iter().enumerate() {
+            let [albedo, shadow, specular] = SNAKE_COLOURS[i].map(faded);
+            ghosts.iter().for_each(|i| frame.draw(
+                snake.mesh(), snake.index(), &shaded_shader,
+                &uniform! { camera: camera, model: *i, view: view, albedo: albedo, shadow: shadow, specular: specular },
+                &ghost_parameters
+            ).unwrap())
+        } }
+
+        //draw walls
+        walls_mat.iter().for_each(|i| frame.draw(
+            wall.mesh(), wall.index(), &shaded_shader,
             &uniform! {
                 camera: camera, model: *i, view: view,
-                albedo:   vec4(0.2, 0.6,  0.3, 1.0),
-                shadow:   vec4(0.2, 0.5,  0.2, 1.0),
-                specular: vec4(0.3, 0.65, 0.4, 1.0)
+                albedo:   vec4(0.55, 0.5,  0.45, 1.0),
+                shadow:   vec4(0.4,  0.37, 0.35, 1.0),
+                specular: vec4(0.65, 0.6,  0.55, 1.0)
             },
             &mesh_parameters
         ).unwrap());
 
-        //draw apples
-        if game.state != State::Win { frame.draw(
-            apple.mesh(), apple.index(), &shaded_shader,
-            &uniform! {
-                camera: camera, model: apple_mat, view: view,
-                albedo:   vec4(1.0, 0.3, 0.5, 1.0),
-                shadow:   vec4(0.6, 0.2, 0.3, 1.0),
-                specular: vec4(1.0, 0.5, 0.6, 1.0)
-            },
-            &mesh_parameters
-        ).unwrap(); }
+        //draw the cell to reach
+        if let Goal::Reach(pos) = game.goal {
+            let pulse = (now.elapsed().as_secs_f32() * 3.0).sin() * 0.05;
+            frame.draw(
+                wall.mesh(), wall.index(), &shaded_shader,
+                &uniform! {
+                    camera: camera, view: view,
+                    model: Mat4::from_pos_and_scale(game.board_to_space(grid(pos)), Vec3::splat(0.3 + pulse)),
+                    albedo:   vec4(1.0, 0.85, 0.3, 1.0),
+                    shadow:   vec4(0.7, 0.55, 0.2, 1.0),
+                    specular: vec4(1.0, 0.95, 0.6, 1.0)
+                },
+                &mesh_parameters
+            ).unwrap();
+        }
+
+        //draw apples, each kind in its own colour
+        if game.state != State::Win {
+            let extras = game.extra_matrices();
+            for (kind, model) in [(AppleKind::Normal, apple_mat)].into_iter().chain(extras) {
+                let [albedo, shadow, specular] = APPLE_COLOURS[kind as usize];
+                frame.draw(
+                    apple.mesh(), apple.index(), &shaded_shader,
+                    &uniform! { camera: camera, model: model, view: view, albedo: albedo, shadow: shadow, specular: specular },
+                    &mesh_parameters
+                ).unwrap();
+            }
+        }
         
         // apply fxaa
         let mut frame = display.draw();
@@ -310,19 +699,66 @@ fn main() {
             image_params: &image_parameters, view2d, frame: &mut frame
         };
         // draw ui
-        match game.state {
-            State::Alive => (),
-            State::Win => image.draw_simple(&win_tex, Vec2::ZERO, 0.5),
+        match (game.state, &rebinding) {
+            (State::Alive, _) => {
+                // how far off the goal is
+                image.draw_frame(&goals_tex, Goal::KINDS.len(), game.goal.index(), vec2(0.1 - right_side, 0.9), 0.05);
+                image.draw_number(&digits_tex, game.goal_left(0), vec2(0.3 - right_side, 0.9), 0.04);
+                if game.snakes.len() == 1 {
+                    image.draw_number(&digits_tex, game.snakes[0].score.points, vec2(right_side - 0.3, 0.9), 0.04);
+                } else { for (i, snake) in game.snakes.iter().enumerate() {
+                    // each player's score next to their colour, dead ones lower down
+                    let y = 0.9 - i as f32 * 0.13;
+                    image.draw_frame(&players_tex, MAX_PLAYERS, i, vec2(right_side - 0.55, y), 0.04);
+                    if snake.alive { image.draw_number(&digits_tex, snake.score.points, vec2(right_side - 0.3, y), 0.04) }
+                } }
+                image.draw_frame(&modes_tex, ControlMode::ALL.len(), control_mode as usize, vec2(0.1 - right_side, 0.75), 0.04);
+                if autopilot.is_some() { image.draw_text(&font_tex, "autopilot", vec2(0.1 - right_side, 0.62), 0.025) }
+            },
+            (_, Some(screen)) => draw_rebinding(&mut image, &font_tex, screen, &binds, right_side),
+            (State::Win, _) => {
+                image.draw_simple(&win_tex, Vec2::ZERO, 0.5);
+                draw_score(&mut image, &digits_tex, &best_tex, game.snakes[0].score.points, new_record == Some(0), now.elapsed().as_secs_f32());
+                if let (Some(winner), true) = (game.winner, game.snakes.len() > 1) {
+                    let bounce = (now.elapsed().as_secs_f32() * 6.0).sin().abs() * 0.03;
+                    image.draw_frame(&players_tex, MAX_PLAYERS, winner, vec2(0.0, 0.45 + bounce), 0.1);
+                }
+                image.draw_number(&digits_tex, game.seed(), vec2(0.0, -0.7), 0.04);
+                if let Some(index) = campaign_level {
+                    image.draw_number(&digits_tex, index as u64 + 1, vec2(0.0, 0.7), 0.08);
+                }
+            },
             _ => {
                 let elapsed = now.elapsed().as_secs_f32();
 
                 //draw controls
                 image.draw_simple(&controls_tex, vec2(0.6 - right_side, 0.35), 0.6);
+                image.draw_frame(&modes_tex, ControlMode::ALL.len(), control_mode as usize, vec2(0.1 - right_side, -0.9), 0.05);
+                for i in 0..level.snakes.len() {
+                    image.draw_frame(&players_tex, MAX_PLAYERS, i, vec2(0.3 - right_side + i as f32 * 0.12, -0.9), 0.04);
+                }
                 image.draw_simple(&map_tex, vec2(right_side - 0.4, 0.25), 0.7);
                 image.draw_simple(&start_tex, vec2(0.0, elapsed.sin()*0.05), 0.25);
                 if game.state != State::Wait{
                     image.draw_simple(&re_tex, vec2(-0.55, (elapsed-0.5).sin()*0.06 - 0.15), 0.1);
+                    draw_score(&mut image, &digits_tex, &best_tex, game.snakes[0].score.points, new_record == Some(0), elapsed);
+                    image.draw_number(&digits_tex, game.seed(), vec2(0.0, -0.7), 0.04);
+                }
+                // the best score for the game that would start
+                if let Some(best) = high_scores.best(&snake3d_core::score_key(&level, speed)) {
+                    image.draw_simple(&best_tex, vec2(0.0, 0.88), 0.04);
+                    image.draw_number(&digits_tex, best.points, vec2(0.0, 0.75), 0.04);
                 }
+                if let Some(index) = campaign_level {
+                    let pos = vec2(right_side - 0.4, -0.55 + (elapsed * 4.0).sin() * 0.02);
+                    image.draw_number(&digits_tex, index as u64 + 1, pos, 0.06);
+                } else { for (i, size) in dims.iter().enumerate() {
+                    let bob = if axis == i { (elapsed * 4.0).sin() * 0.02 } else { 0.0 };
+                    let pos = vec2(right_side - 0.6 + i as f32 * 0.2, -0.55 + bob);
+                    image.draw_number(&digits_tex, *size as u64, pos, 0.04);
+                    if wrap[i] { image.draw_simple(&wrap_tex, pos - vec2(0.0, 0.12), 0.04) }
+                } }
+                image.draw_frame(&goals_tex, Goal::KINDS.len(), level.goal.index(), vec2(right_side - 0.85, -0.55), 0.05);
                 image.draw(
                     &speed_tex, vec2(right_side - 0.6 / 4.0, -0.9),
                     vec2(0.6 / 4.0, 0.1), vec2(1.0, 1.0 / 7.0),
@@ -348,3 +784,61 @@ fn speed_timer(value: i8) -> f32 {
         _ => unreachable!()
     }
 }
+// replays play at the speed they were made at, but none slower than the default. that
+// way one made at speed 6, which only moves on input, still plays by itself. the speed
+// comes from the file so anything out of range is clamped.
+fn playback_timer(value: i8) -> f32 {
+    speed_timer(value.clamp(0, 6)).min(speed_timer(3))
+}
+// every action with its inputs, the selected one marked and the one that blocked the
+// last input blinking. only the rows around the selected one fit on screen.
+fn draw_rebinding(image: &mut ImageDrawer, font: &Texture2d, screen: &Rebinding, binds: &bindings::Binds, right_side: f32) {
+    const ROWS: usize = 24;
+    let first = screen.selected.saturating_sub(ROWS / 2).min(ACTIONS - ROWS);
+    for (i, action) in bindings::Action::ALL.iter().enumerate().skip(first).take(ROWS) {
+        let y = 0.9 - (i - first) as f32 * 0.075;
+        let hidden = screen.conflict.is_some_and(|(other, time)| {
+            let time = time.elapsed().as_secs_f32();
+            other == *action && time < 1.0 && (time * 8.0) as u32 & 1 == 0
+        });
+        if !hidden {
+            image.draw_text(font, &action.name().replace('_', " "), vec2(0.1 - right_side, y), 0.025);
+        }
+        let inputs: Vec<String> = binds[i].iter().map(|i| bindings::short_name(*i)).collect();
+        let mut inputs = inputs.join(" ");
+        if screen.selected == i {
+            image.draw_text(font, "-", vec2(0.02 - right_side, y), 0.025);
+            if screen.listening { inputs += " ?" }
+        }
+        image.draw_text(font, &inputs, vec2(0.0, y), 0.025);
+    }
+}
+// the score at the end of a game, bouncing with a trophy over it if it's a new record.
+fn draw_score(image: &mut ImageDrawer, digits: &Texture2d, best: &Texture2d, points: u64, record: bool, elapsed: f32) {
+    if record {
+        let bounce = (elapsed * 6.0).sin().abs() * 0.03;
+        image.draw_simple(best, vec2(0.0, -0.42 + bounce), 0.05);
+        image.draw_number(digits, points, vec2(0.0, -0.55), 0.07 + bounce / 3.0);
+    } else {
+        image.draw_number(digits, points, vec2(0.0, -0.55), 0.06);
+    }
+}
+fn default_ups(game: &Board) -> Vec<Direction> {
+    game.snakes.iter().map(|i| controls::default_up(i.direction)).collect()
+}
+fn apples_eaten(game: &Board) -> usize {
+    game.snakes.iter().map(|i| i.apples).sum()
+}
+// goals to pick from when not playing a level, sized to the board.
+fn free_goal(kind: usize, dims: [usize; 3]) -> Goal {
+    let cells = dims.iter().product::<usize>();
+    match kind {
+        0 => Goal::Perfect,
+        1 => Goal::Length(cells.div_ceil(2)),
+        2 => Goal::Apples(10),
+        3 => Goal::Survive(cells * 2),
+        // the far corner from where the snake starts
+        4 => Goal::Reach(snake3d_core::ivec3(dims[0] as i32 - 1, dims[1] as i32 - 1, dims[2] as i32 - 1)),
+        _ => Goal::Score(200)
+    }
+}
@@ -0,0 +1,155 @@
+use snake3d_core::{Board, Direction, Level, Lockstep, Packet, Replay};
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use crate::cli::Options;
+// how often the latest inputs get sent again when nothing new has been sent, to make up
+// for lost packets, and how long a quiet peer gets before the game gives up on them.
+const RESEND: Duration = Duration::from_millis(50);
+const TIMEOUT: Duration = Duration::from_secs(10);
+// this player's end of an online game. the board is run by `Lockstep`, this just moves
+// its packets about.
+pub struct Session {
+    socket: UdpSocket,
+    // every player's address, none for this one.
+    peers: Vec<Option<SocketAddr>>,
+    pub lockstep: Lockstep,
+    // turns taken since the last direction was picked, like a snake's queue.
+    queue: VecDeque<Direction>,
+    pub queue_depth: usize,
+    heard: Vec<Instant>,
+    sent: Instant,
+    // the host's start packets, sent again to anyone still asking to join.
+    starts: Vec<Vec<u8>>
+}
+impl Session {
+    fn new(socket: UdpSocket, peers: Vec<Option<SocketAddr>>, player: usize, delay: usize, game: &Replay, starts: Vec<Vec<u8>>) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        let heard = vec![Instant::now(); peers.len()];
+        let lockstep = Lockstep::new(&game.board(), player, delay);
+        Ok(Self { socket, peers, lockstep, queue: VecDeque::new(), queue_depth: 1, heard, sent: Instant::now(), starts })
+    }
+    // waits for a player to join for every snake past the first, then starts the game.
+    pub fn host(port: u16, game: &Replay, delay: usize) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        let players = game.level.snakes.len();
+        let mut peers = vec![None];
+        println!("waiting for {} more players on port {port}", players - 1);
+        let mut bytes = [0; 64];
+        while peers.len() < players {
+            let (len, from) = match socket.recv_from(&mut bytes) {
+                Ok(i) => i,
+                Err(i) if i.kind() == ErrorKind::ConnectionReset => continue,
+                Err(i) => return Err(i)
+            };
+            if Packet::from_bytes(&bytes[..len]).ok() != Some(Packet::Join) || peers.contains(&Some(from)) { continue }
+            peers.push(Some(from));
+            println!("{from} joined as player {}", peers.len());
+        }
+        let starts: Vec<Vec<u8>> = (0..players).map(|player| {
+            Packet::Start { player, delay, peers: peers.clone(), game: game.clone() }.to_bytes()
+        }).collect();
+        for (start, peer) in starts.iter().zip(&peers) {
+            if let Some(peer) = peer { socket.send_to(start, peer)?; }
+        }
+        Self::new(socket, peers, 0, delay, game, starts)
+    }
+    // asks the host at `address` for a place until it replies with the game.
+    pub fn join(address: &str) -> io::Result<(Self, Replay)> {
+        let host = address.to_socket_addrs()?.next()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, format!("couldn't find {address}")))?;
+        let socket = UdpSocket::bind(if host.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" })?;
+        socket.set_read_timeout(Some(Duration::from_millis(250)))?;
+        println!("joining {host}");
+        let started = Instant::now();
+        let mut bytes = vec![0; u16::MAX as usize];
+        while started.elapsed() < TIMEOUT {
+            socket.send_to(&Packet::Join.to_bytes(), host)?;
+            let (len, from) = match socket.recv_from(&mut bytes) {
+                Ok(i) => i,
+                Err(i) if matches!(i.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ConnectionReset) => continue,
+                Err(i) => return Err(i)
+            };
+            // other players may already be sending inputs
+            let Ok(Packet::Start { player, delay, mut peers, game }) = Packet::from_bytes(&bytes[..len]) else { continue };
+            if from != host || player == 0 || player >= peers.len() || peers.len() != game.level.snakes.len() { continue }
+            (peers[0], peers[player]) = (Some(host), None);
+            println!("playing as player {}", player + 1);
+            let session = Self::new(socket, peers, player, delay, &game, vec![])?;
+            return Ok((session, game))
+        }
+        Err(io::Error::new(ErrorKind::TimedOut, format!("no reply from {host}")))
+    }
+    // the way this player's snake will be heading once its queued turns are taken.
+    pub fn heading(&self) -> Direction {
+        self.queue.back().copied().unwrap_or(self.lockstep.last_local())
+    }
+    pub fn turn(&mut self, direction: Direction) {
+        if direction == self.heading() || self.queue.len() >= self.queue_depth.max(1) { return }
+        self.queue.push_back(direction);
+    }
+    // picks this player's next direction if it's due and plays the next tick if every
+    // player's direction for it is in. returns whether it did.
+    pub fn step(&mut self, board: &mut Board) -> io::Result<bool> {
+        if self.lockstep.needs_local() {
+            let direction = self.queue.pop_front().unwrap_or(self.lockstep.last_local());
+            self.lockstep.push_local(direction);
+            self.send()?;
+        }
+        Ok(self.lockstep.step(board))
+    }
+    fn send(&mut self) -> io::Result<()> {
+        let packet = self.lockstep.packet().to_bytes();
+        for peer in self.peers.iter().flatten() { self.socket.send_to(&packet, peer)?; }
+        self.sent = Instant::now();
+        Ok(())
+    }
+    // takes in everything that's arrived and sends the latest inputs again if it's been
+    // a while. fails once the boards drift apart or a peer stops answering.
+    pub fn poll(&mut self) -> io::Result<()> {
+        let mut bytes = [0; 1024];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut bytes) {
+                Ok(i) => i,
+                Err(i) if i.kind() == ErrorKind::WouldBlock => break,
+                Err(i) if i.kind() == ErrorKind::ConnectionReset => continue,
+                Err(i) => return Err(i)
+            };
+            let Some(index) = self.peers.iter().position(|i| *i == Some(from)) else { continue };
+            self.heard[index] = Instant::now();
+            match Packet::from_bytes(&bytes[..len]) {
+                // their start went missing
+                Ok(Packet::Join) => if let Some(start) = self.starts.get(index) { self.socket.send_to(start, from)?; },
+                Ok(packet @ Packet::Input { player, .. }) if player == index => self.lockstep.receive(&packet),
+                _ => ()
+            }
+        }
+        if self.sent.elapsed() >= RESEND { self.send()? }
+        if let Some(tick) = self.lockstep.desync {
+            return Err(io::Error::other(format!("the game went out of sync on tick {tick}")))
+        }
+        if let Some(player) = (0..self.peers.len()).find(|i| self.peers[*i].is_some() && self.heard[*i].elapsed() >= TIMEOUT) {
+            return Err(io::Error::new(ErrorKind::TimedOut, format!("player {} stopped responding", player + 1)))
+        }
+        Ok(())
+    }
+}
+// the session and game for --host or --join, if either was given.
+pub fn connect(options: &Options, level: &Level) -> io::Result<Option<(Session, Replay)>> {
+    let (mut session, game) = match (options.host, &options.join) {
+        (_, Some(address)) => Session::join(address)?,
+        (Some(port), _) => {
+            if level.snakes.len() < 2 {
+                return Err(io::Error::new(ErrorKind::InvalidInput, "online games need more than one snake, try --players 2"))
+            }
+            let seed = options.seed.unwrap_or_else(rand::random);
+            let mut game = Replay::new(level.clone(), seed, level.speed.unwrap_or(options.speed));
+            game.allow_reverse = options.reverse;
+            (Session::host(port, &game, options.delay)?, game)
+        },
+        _ => return Ok(None)
+    };
+    session.queue_depth = options.input_buffer;
+    Ok(Some((session, game)))
+}
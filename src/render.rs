@@ -0,0 +1,148 @@
+use std::f32::consts::PI;
+use thin_engine::{prelude::*, glium_types::vectors::*};
+use snake3d_core::{AppleKind, Board, Direction, MAX_PLAYERS};
+use crate::load::Mesh;
+// albedo, shadow and specular for each player's snake, matching sprites/players.png.
+pub const SNAKE_COLOURS: [[Vec4; 3]; MAX_PLAYERS] = [
+    [vec4(0.2, 0.6, 0.3, 1.0), vec4(0.2,  0.5,  0.2, 1.0), vec4(0.3,  0.65, 0.4,  1.0)],
+    [vec4(0.2, 0.4, 0.8, 1.0), vec4(0.15, 0.3,  0.6, 1.0), vec4(0.35, 0.55, 0.9,  1.0)],
+    [vec4(0.9, 0.5, 0.15, 1.0), vec4(0.7, 0.35, 0.1, 1.0), vec4(1.0,  0.65, 0.3,  1.0)],
+    [vec4(0.6, 0.3, 0.8, 1.0), vec4(0.45, 0.2,  0.6, 1.0), vec4(0.7,  0.45, 0.9,  1.0)]
+];
+// albedo, shadow and specular for each kind of apple, in the order of `AppleKind::ALL`.
+pub const APPLE_COLOURS: [[Vec4; 3]; AppleKind::ALL.len()] = [
+    [vec4(1.0, 0.3,  0.5,  1.0), vec4(0.6,  0.2,  0.3,  1.0), vec4(1.0, 0.5,  0.6,  1.0)],
+    [vec4(1.0, 0.8,  0.2,  1.0), vec4(0.7,  0.5,  0.1,  1.0), vec4(1.0, 0.95, 0.6,  1.0)],
+    [vec4(0.5, 0.35, 0.9,  1.0), vec4(0.3,  0.2,  0.6,  1.0), vec4(0.7, 0.6,  1.0,  1.0)],
+    [vec4(0.3, 0.9,  1.0,  1.0), vec4(0.15, 0.55, 0.7,  1.0), vec4(0.7, 1.0,  1.0,  1.0)],
+    [vec4(0.6, 0.4,  0.2,  1.0), vec4(0.4,  0.25, 0.1,  1.0), vec4(0.8, 0.6,  0.35, 1.0)]
+];
+// the rules use their own vector type so they can run without the engine.
+pub fn grid(value: snake3d_core::IVec3) -> IVec3 {
+    ivec3(value.x, value.y, value.z)
+}
+pub trait DirectionRender {
+    fn rot(&self) -> Quaternion;
+}
+impl DirectionRender for Direction {
+    fn rot(&self) -> Quaternion{
+        match self {
+            Direction::Forward => Quaternion::from_y_rot( PI      ),
+            Direction::Back    => Quaternion::from_y_rot( 0.0     ),
+            Direction::Left    => Quaternion::from_y_rot( PI / 2.0) ,
+            Direction::Right   => Quaternion::from_y_rot(-PI / 2.0),
+            Direction::Up      => Quaternion::from_x_rot( PI / 2.0),
+            Direction::Down    => Quaternion::from_x_rot(-PI / 2.0),
+        }
+    }
+}
+pub trait BoardRender {
+    fn board_to_space(&self, value: IVec3) -> Vec3;
+    fn shadow_matrix(&self, value: IVec3) -> Mat4;
+    fn matrices(&self) -> (Mat4, Vec<Vec<Mat4>>, Vec<IVec3>);
+    fn ghost_matrices(&self) -> Vec<Vec<Mat4>>;
+    fn wall_matrices(&self) -> Vec<Mat4>;
+    fn extra_matrices(&self) -> Vec<(AppleKind, Mat4)>;
+}
+impl BoardRender for Board {
+    fn board_to_space(&self, value: IVec3) -> Vec3 {
+        let IVec3 { x, y, z } = value;
+        let IVec3 { x: sx, y: sy, z: sz } = grid(self.board_size()) - IVec3::ONE;
+        let x_offset = sx as f32 / 2.0;
+        let y_offset = sy as f32 / 2.0;
+        let z_offset = sz as f32 / 2.0;
+        vec3(x as f32 - x_offset, z as f32 - z_offset, y as f32 - y_offset)
+    }
+    fn shadow_matrix(&self, value: IVec3) -> Mat4 {
+        let IVec3 { x, y, z: s } = value;
+        let IVec3 { x: width, y: depth, z: height } = grid(self.board_size());
+        let x = x as f32 - (width  - 1) as f32 / 2.0;
+        let y = y as f32 - (depth  - 1) as f32 / 2.0;
+        let z = -0.5 - (height - 1) as f32 / 2.0;
+        Mat4::from_transform(
+            vec3(x, z, y), Vec3::splat(0.5 - (f32::sqrt(s as f32)*0.05).min(0.5)),
+            Quaternion::from_x_rot(PI/2.0)
+        )
+    }
+    // the apple, each snake's segments and the shadows.
+    fn matrices(&self) -> (Mat4, Vec<Vec<Mat4>>, Vec<IVec3>) {
+        let size = self.board_size();
+        let apple_pos = grid(self.apple_pos);
+        let mut shadows = vec![vec![None; size.y as usize]; size.x as usize];
+        let mut shadow = vec![apple_pos.truncate()];
+        shadows[apple_pos.x as usize][apple_pos.y as usize] = Some(apple_pos.z);
+        let snakes = self.snakes.iter().map(|snake| {
+            snake.body.iter().map(|i| Mat4::from_pos(self.board_to_space(grid(*i)))).collect()
+        }).collect();
+        let bodies = self.snakes.iter().flat_map(|i| &i.body);
+        let extras = self.extras.iter().map(|i| &i.pos);
+        for i in bodies.chain(self.walls()).chain(extras).map(|i| grid(*i)) {
+            let ref_height: &mut Option<i32> = &mut shadows[i.x as usize][i.y as usize];
+            if let Some(height) = ref_height {
+                *ref_height = Some((*height).min(i.z));
+            } else {
+                shadow.push(i.truncate());
+                *ref_height = Some(i.z);
+            }
+        }
+
+        let apple_mat = Mat4::from_pos(self.board_to_space(apple_pos));
+        (apple_mat, snakes, shadow.into_iter().map(|i| {
+            let height = shadows[i.x as usize][i.y as usize];
+            i.extend(height.unwrap())
+        }).collect())
+    }
+    // copies of the segments on a wrapping face drawn just past the opposite face,
+    // to show where each snake will come out.
+    fn ghost_matrices(&self) -> Vec<Vec<Mat4>> {
+        let size = grid(self.board_size());
+        self.snakes.iter().map(|snake| {
+            let mut ghosts = vec![];
+            for i in snake.body.iter().map(|i| grid(*i)) {
+                let axes = [(i.x, size.x, IVec3::X), (i.y, size.y, IVec3::Y), (i.z, size.z, IVec3::Z)];
+                for ((value, size, axis), wrap) in axes.into_iter().zip(self.wrap) {
+                    if !wrap { continue }
+                    if value == 0        { ghosts.push(Mat4::from_pos(self.board_to_space(i + axis.scale(size)))) }
+                    if value == size - 1 { ghosts.push(Mat4::from_pos(self.board_to_space(i - axis.scale(size)))) }
+                }
+            }
+            ghosts
+        }).collect()
+    }
+    fn wall_matrices(&self) -> Vec<Mat4> {
+        self.walls().iter().map(|i| Mat4::from_pos(self.board_to_space(grid(*i)))).collect()
+    }
+    // the extra apples, with timed ones shrinking away as they rot.
+    fn extra_matrices(&self) -> Vec<(AppleKind, Mat4)> {
+        self.extras.iter().map(|apple| {
+            let pos = self.board_to_space(grid(apple.pos));
+            let scale = apple.ticks_left.map_or(1.0, |i| (i as f32 / snake3d_core::ROT_TICKS as f32).sqrt().max(0.4));
+            (apple.kind, Mat4::from_pos_and_scale(pos, Vec3::splat(scale)))
+        }).collect()
+    }
+}
+// the inside of a box around the board with uvs in cells, so the checker texture lines
+// up with the grid on every face no matter the board shape. draw with a `size` of 0.5.
+pub fn board_mesh(width: usize, depth: usize, height: usize, display: &thin_engine::Display) -> Mesh {
+    let extent = vec3(width as f32, height as f32, depth as f32);
+    // (inward normal, a, b) where a cross b is the normal so the winding faces inwards.
+    let faces = [
+        (Vec3::Y, Vec3::Z, Vec3::X), (-Vec3::Y, Vec3::X, Vec3::Z),
+        (Vec3::X, Vec3::Y, Vec3::Z), (-Vec3::X, Vec3::Z, Vec3::Y),
+        (Vec3::Z, Vec3::X, Vec3::Y), (-Vec3::Z, Vec3::Y, Vec3::X)
+    ];
+    let (mut indices, mut pos, mut normals, mut uvs) = (vec![], vec![], vec![], vec![]);
+    for (normal, a, b) in faces {
+        let centre = normal.scale(-extent.dot(normal).abs() / 2.0);
+        let (a_len, b_len) = (extent.dot(a), extent.dot(b));
+        let start = pos.len() as u32;
+        for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            pos.push((centre + a.scale((u - 0.5) * a_len) + b.scale((v - 0.5) * b_len)).into());
+            normals.push(normal.into());
+            // offset by a cell so the floor matches the shadow colours.
+            uvs.push(vec2(u * a_len + 1.0, v * b_len).into());
+        }
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+    Mesh::new(&indices, &pos, &normals, &uvs, display)
+}